@@ -0,0 +1,343 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{AocError, Result};
+use crate::find_puzzle;
+
+
+/// Accepted answers of a day for one input file (the example or the real puzzle input).
+///
+/// A part without a recorded answer is not checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expected {
+    pub day: u8,
+    pub name: String,
+    pub input: PathBuf,
+    pub part_1: Option<String>,
+    pub part_2: Option<String>,
+}
+
+impl Expected {
+    fn part(&self, part: u8) -> Option<&str> {
+        match part {
+            1 => self.part_1.as_deref(),
+            _ => self.part_2.as_deref(),
+        }
+    }
+}
+
+
+/// Result of checking one part against its recorded answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Pass,
+    Mismatch { expected: String, actual: String },
+    Error(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub day: u8,
+    pub name: String,
+    pub part: u8,
+    pub outcome: Outcome,
+}
+
+
+/// Load the answers file, see `answers.toml` at the root of the repository for the format.
+pub fn load<P: AsRef<Path>>(filename: P) -> Result<Vec<Expected>> {
+    let input = fs::read_to_string(filename)?;
+    parse(&input)
+}
+
+/// Parse the answers: a small subset of TOML made of `[dayNN.name]` sections holding an `input`
+/// path and the `part_1` / `part_2` answers, as integers or strings.
+pub fn parse(input: &str) -> Result<Vec<Expected>> {
+    let items = crate::parse::finish(input, parser::parse_items)?;
+
+    let mut entries: Vec<Expected> = Vec::new();
+    for item in items {
+        match item {
+            parser::Item::Section { at, day, name } => {
+                if find_puzzle(day).is_none() {
+                    return Err(AocError::parse_error(input, at, format!("day {day} is not implemented")));
+                }
+                if entries.iter().any(|e| e.day == day && e.name == name) {
+                    return Err(AocError::parse_error(input, at, format!("duplicate section day{day:02}.{name}")));
+                }
+                entries.push(Expected {
+                    day,
+                    name: name.to_string(),
+                    input: PathBuf::new(),
+                    part_1: None,
+                    part_2: None,
+                });
+            }
+            parser::Item::Pair { at, key, value } => {
+                let entry = entries.last_mut()
+                    .ok_or_else(|| AocError::parse_error(input, at, "key outside of a [dayNN.name] section"))?;
+                match key {
+                    "input" => entry.input = PathBuf::from(value),
+                    "part_1" => entry.part_1 = Some(value),
+                    "part_2" => entry.part_2 = Some(value),
+                    _ => return Err(AocError::parse_error(input, at, format!("unknown key '{key}'"))),
+                }
+            }
+        }
+    }
+
+    if let Some(entry) = entries.iter().find(|e| e.input.as_os_str().is_empty()) {
+        return Err(AocError::invalid(format!("no input for day{:02}.{}", entry.day, entry.name)));
+    }
+    Ok(entries)
+}
+
+
+/// Solve every recorded part and compare the answers; trailing whitespace is ignored.
+pub fn verify(expected: &Expected) -> Vec<Check> {
+    let parts: Vec<u8> = (1..=2).filter(|&part| expected.part(part).is_some()).collect();
+    let check = |part: u8, outcome: Outcome| Check {
+        day: expected.day,
+        name: expected.name.clone(),
+        part,
+        outcome,
+    };
+
+    let Some(puzzle) = find_puzzle(expected.day) else {
+        let message = format!("day {} is not implemented", expected.day);
+        return parts.into_iter().map(|part| check(part, Outcome::Error(message.clone()))).collect();
+    };
+    let parsed = fs::read_to_string(&expected.input)
+        .map_err(AocError::from)
+        .and_then(|input| puzzle.parse(&input));
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            let message = format!("{}: {e}", expected.input.display());
+            return parts.into_iter().map(|part| check(part, Outcome::Error(message.clone()))).collect();
+        }
+    };
+
+    parts.into_iter()
+        .map(|part| {
+            let wanted = expected.part(part).unwrap().trim_end();
+            let outcome = match parsed.solve(part) {
+                Ok(answer) => {
+                    let actual = answer.to_string();
+                    if actual.trim_end() == wanted {
+                        Outcome::Pass
+                    } else {
+                        Outcome::Mismatch { expected: wanted.to_string(), actual: actual.trim_end().to_string() }
+                    }
+                }
+                Err(e) => Outcome::Error(e.to_string()),
+            };
+            check(part, outcome)
+        })
+        .collect()
+}
+
+
+mod parser {
+
+    use nom::IResult;
+    use nom::branch::alt;
+    use nom::bytes::complete::{tag, take_until, take_while1, take_while_m_n};
+    use nom::character::complete::{char, digit1, line_ending, none_of, not_line_ending, space0};
+    use nom::combinator::{eof, map, map_res, opt, recognize, value};
+    use nom::multi::{many0, many_till};
+    use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
+
+    pub(super) enum Item<'a> {
+        // `at` is where the item starts in the file, to locate errors
+        Section { at: &'a str, day: u8, name: &'a str },
+        Pair { at: &'a str, key: &'a str, value: String },
+    }
+
+    fn is_name_char(c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_'
+    }
+
+    fn end_of_line(input: &str) -> IResult<&str, ()> {
+        value(
+            (),
+            tuple((
+                space0,
+                opt(preceded(char('#'), not_line_ending)),
+                alt((line_ending, eof)),
+            ))
+        )(input)
+    }
+
+    fn parse_section(input: &str) -> IResult<&str, Item<'_>> {
+        map(
+            delimited(
+                tag("[day"),
+                separated_pair(
+                    map_res(take_while_m_n(2, 2, |c: char| c.is_ascii_digit()), str::parse::<u8>),
+                    char('.'),
+                    take_while1(is_name_char)
+                ),
+                char(']')
+            ),
+            move |(day, name)| Item::Section { at: input, day, name }
+        )(input)
+    }
+
+    fn parse_escaped_char(input: &str) -> IResult<&str, char> {
+        preceded(
+            char('\\'),
+            alt((
+                value('\n', char('n')),
+                value('\\', char('\\')),
+                value('"', char('"')),
+            ))
+        )(input)
+    }
+
+    // "basic string" with \n, \\ and \" escapes
+    fn parse_basic_string(input: &str) -> IResult<&str, String> {
+        delimited(
+            char('"'),
+            map(
+                many0(alt((parse_escaped_char, none_of("\"\\\n")))),
+                |chars| chars.into_iter().collect()
+            ),
+            char('"')
+        )(input)
+    }
+
+    // '''multi-line literal''', the newline right after the opening quotes is not part of it
+    fn parse_multiline_literal(input: &str) -> IResult<&str, String> {
+        map(
+            delimited(
+                pair(tag("'''"), opt(line_ending)),
+                take_until("'''"),
+                tag("'''")
+            ),
+            |s: &str| s.replace("\r\n", "\n")
+        )(input)
+    }
+
+    fn parse_value(input: &str) -> IResult<&str, String> {
+        alt((
+            parse_multiline_literal,
+            parse_basic_string,
+            map(recognize(pair(opt(char('-')), digit1)), str::to_string),
+        ))(input)
+    }
+
+    fn parse_pair(input: &str) -> IResult<&str, Item<'_>> {
+        map(
+            separated_pair(
+                take_while1(is_name_char),
+                tuple((space0, char('='), space0)),
+                parse_value
+            ),
+            move |(key, value)| Item::Pair { at: input, key, value }
+        )(input)
+    }
+
+    pub(super) fn parse_items(input: &str) -> IResult<&str, Vec<Item<'_>>> {
+        map(
+            many_till(
+                alt((
+                    map(end_of_line, |_| None),
+                    map(
+                        terminated(preceded(space0, alt((parse_section, parse_pair))), end_of_line),
+                        Some
+                    ),
+                )),
+                eof
+            ),
+            |(items, _)| items.into_iter().flatten().collect()
+        )(input)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use std::path::PathBuf;
+
+    use super::{Expected, Outcome};
+
+    #[test]
+    fn test_parse() {
+        let answers = super::parse(concat!(
+            "# accepted answers\n",
+            "[day01.example]\n",
+            "input = \"test_input/day01.txt\"\n",
+            "part_1 = 24000  # first part\n",
+            "\n",
+            "[day10.real]\n",
+            "input = \"input/day10.txt\"\n",
+            "part_2 = '''\n",
+            "##..\n",
+            "..##\n",
+            "'''\n",
+        )).unwrap();
+        assert_eq!(answers, vec![
+            Expected {
+                day: 1,
+                name: "example".to_string(),
+                input: PathBuf::from("test_input/day01.txt"),
+                part_1: Some("24000".to_string()),
+                part_2: None,
+            },
+            Expected {
+                day: 10,
+                name: "real".to_string(),
+                input: PathBuf::from("input/day10.txt"),
+                part_1: None,
+                part_2: Some("##..\n..##\n".to_string()),
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            super::parse("[day01.real]\ninput = \"x\"\nanswer = 3\n").unwrap_err().to_string(),
+            "parse error at line 3, column 1: unknown key 'answer'"
+        );
+        assert_eq!(
+            super::parse("[day30.real]\n").unwrap_err().to_string(),
+            "parse error at line 1, column 1: day 30 is not implemented"
+        );
+        assert!(super::parse("part_1 = 3\n").is_err());
+        assert!(super::parse("[day01.real]\npart_1 = 3\n").is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut expected = Expected {
+            day: 1,
+            name: "example".to_string(),
+            input: PathBuf::from("test_input/day01.txt"),
+            part_1: Some("24000".to_string()),
+            part_2: Some("1".to_string()),
+        };
+        let checks = super::verify(&expected);
+        assert_eq!(checks[0].outcome, Outcome::Pass);
+        assert_eq!(checks[1].outcome, Outcome::Mismatch { expected: "1".to_string(), actual: "45000".to_string() });
+
+        expected.input = PathBuf::from("test_input/missing.txt");
+        assert!(matches!(super::verify(&expected)[0].outcome, Outcome::Error(_)));
+
+        expected.day = 30;
+        assert_eq!(super::verify(&expected)[0].outcome, Outcome::Error("day 30 is not implemented".to_string()));
+    }
+
+    // Check every recorded answer, so that refactors are checked against the real puzzle inputs
+    #[test]
+    fn test_recorded_answers() {
+        let failures: Vec<_> = super::load("answers.toml").unwrap()
+            .iter()
+            .flat_map(super::verify)
+            .filter(|check| check.outcome != Outcome::Pass)
+            .collect();
+        assert_eq!(failures, vec![]);
+    }
+}
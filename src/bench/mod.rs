@@ -0,0 +1,209 @@
+use std::fmt::Write;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::error::{AocError, Result};
+use crate::solution::Puzzle;
+
+
+/// Wall time of repeated runs of one stage (parse, part 1 or part 2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// Summarize the samples, which must not be empty.
+    pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty(), "no samples to summarize");
+        samples.sort_unstable();
+        let middle = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) {
+            (samples[middle - 1] + samples[middle]) / 2
+        } else {
+            samples[middle]
+        };
+        Self {
+            min: samples[0],
+            median,
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+
+/// Timings of the parse and of both parts of a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayBench {
+    pub day: u8,
+    pub parse: Stats,
+    pub part_1: Stats,
+    pub part_2: Stats,
+}
+
+impl DayBench {
+    fn stages(&self) -> [(&'static str, &Stats); 3] {
+        [("parse", &self.parse), ("part_1", &self.part_1), ("part_2", &self.part_2)]
+    }
+}
+
+
+/// Run the parse and both parts of `puzzle` on `input`, `iterations` times each.
+pub fn bench_puzzle(puzzle: &dyn Puzzle, input: &str, iterations: usize) -> Result<DayBench> {
+    if iterations == 0 {
+        return Err(AocError::invalid("at least one iteration is needed"));
+    }
+
+    let mut samples = Vec::with_capacity(iterations);
+    let mut parsed = None;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        parsed = Some(puzzle.parse(input)?);
+        samples.push(start.elapsed());
+    }
+    let parse = Stats::from_samples(samples);
+
+    // Both parts are solved from the same parsed input, as the runner does
+    let parsed = parsed.unwrap();
+    let mut parts = Vec::with_capacity(2);
+    for part in 1..=2 {
+        let mut samples = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            let start = Instant::now();
+            parsed.solve(part)?;
+            samples.push(start.elapsed());
+        }
+        parts.push(Stats::from_samples(samples));
+    }
+
+    Ok(DayBench { day: puzzle.day(), parse, part_1: parts[0], part_2: parts[1] })
+}
+
+
+/// Output format of the benchmark results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Text,
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Self::Text),
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(AocError::invalid(format!("unknown format '{s}', expected text, csv or json"))),
+        }
+    }
+}
+
+
+/// Render the results; CSV and JSON times are in nanoseconds so they can be compared between runs.
+pub fn render(results: &[DayBench], format: Format) -> String {
+    let mut s = String::new();
+    match format {
+        Format::Text => {
+            writeln!(s, "{:<4} {:<7} {:>12} {:>12} {:>12}", "day", "stage", "min", "median", "max").unwrap();
+            for bench in results {
+                for (stage, stats) in bench.stages() {
+                    writeln!(
+                        s, "{:<4} {:<7} {:>12} {:>12} {:>12}",
+                        format!("{:02}", bench.day),
+                        stage,
+                        format!("{:.2?}", stats.min),
+                        format!("{:.2?}", stats.median),
+                        format!("{:.2?}", stats.max),
+                    ).unwrap();
+                }
+            }
+        }
+        Format::Csv => {
+            s.push_str("day,stage,min_ns,median_ns,max_ns\n");
+            for bench in results {
+                for (stage, stats) in bench.stages() {
+                    writeln!(
+                        s, "{},{},{},{},{}",
+                        bench.day, stage, stats.min.as_nanos(), stats.median.as_nanos(), stats.max.as_nanos()
+                    ).unwrap();
+                }
+            }
+        }
+        Format::Json => {
+            let records: Vec<String> = results.iter()
+                .flat_map(|bench| bench.stages().map(|(stage, stats)| {
+                    format!(
+                        r#"  {{"day": {}, "stage": "{}", "min_ns": {}, "median_ns": {}, "max_ns": {}}}"#,
+                        bench.day, stage, stats.min.as_nanos(), stats.median.as_nanos(), stats.max.as_nanos()
+                    )
+                }))
+                .collect();
+            if records.is_empty() {
+                s.push_str("[]\n");
+            } else {
+                writeln!(s, "[\n{}\n]", records.join(",\n")).unwrap();
+            }
+        }
+    }
+    s
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use std::time::Duration;
+
+    use super::{DayBench, Format, Stats};
+
+    fn ns(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&v| Duration::from_nanos(v)).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::from_samples(ns(&[30, 10, 20]));
+        assert_eq!(stats, Stats {
+            min: Duration::from_nanos(10),
+            median: Duration::from_nanos(20),
+            max: Duration::from_nanos(30),
+        });
+        assert_eq!(Stats::from_samples(ns(&[40, 10, 20, 30])).median, Duration::from_nanos(25));
+    }
+
+    #[test]
+    fn test_bench_puzzle() {
+        let puzzle = crate::find_puzzle(1).unwrap();
+        let bench = super::bench_puzzle(puzzle.as_ref(), "1\n2\n\n3\n\n4\n", 3).unwrap();
+        assert_eq!(bench.day, 1);
+        assert!(bench.parse.min <= bench.parse.median && bench.parse.median <= bench.parse.max);
+        assert!(super::bench_puzzle(puzzle.as_ref(), "x\n", 3).is_err());
+        assert!(super::bench_puzzle(puzzle.as_ref(), "1\n", 0).is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let stats = Stats::from_samples(ns(&[1000, 2000, 3000]));
+        let results = vec![DayBench { day: 4, parse: stats, part_1: stats, part_2: stats }];
+        assert_eq!(
+            super::render(&results, Format::Csv),
+            concat!(
+                "day,stage,min_ns,median_ns,max_ns\n",
+                "4,parse,1000,2000,3000\n",
+                "4,part_1,1000,2000,3000\n",
+                "4,part_2,1000,2000,3000\n",
+            )
+        );
+        assert!(super::render(&results, Format::Json).starts_with(
+            "[\n  {\"day\": 4, \"stage\": \"parse\", \"min_ns\": 1000, \"median_ns\": 2000, \"max_ns\": 3000},\n"
+        ));
+        assert_eq!("json".parse::<Format>().unwrap(), Format::Json);
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
@@ -1,51 +1,80 @@
-use std::{fs::File, path::Path};
-use std::io::{self, BufRead};
-
-
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u32 {
-    let file = File::open(filename).unwrap();
-    let mut calories_max = 0u32;
-    let mut calories = 0u32;
-    for line in io::BufReader::new(file).lines() {
-        match line.unwrap().as_str() {
-            "" =>  {
-                calories_max = calories_max.max(calories);
-                calories = 0;
-            },
-            svalue => {
-                let v = svalue.parse::<u32>().unwrap();
-                calories += v;
-            },
-        }
+use std::path::Path;
+
+use crate::error::{AocError, Result};
+use crate::solution::{Answer, Solution};
+
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    type Input = Vec<Vec<u32>>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_elves(input)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_2(input)?.into())
     }
-    calories_max = calories_max.max(calories);
-    calories_max
 }
 
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> u32 {
-    let file = File::open(filename).unwrap();
-    let mut elves_calories: Vec<u32> = Vec::new();
-    let mut calories = 0u32;
-    for line in io::BufReader::new(file).lines() {
-        match line.unwrap().as_str() {
-            "" =>  {
-                elves_calories.push(calories);
-                calories = 0;
-            },
+// Calories carried by each elf, elves are separated by one or more empty lines
+fn parse_elves(input: &str) -> Result<Vec<Vec<u32>>> {
+    let mut elves: Vec<Vec<u32>> = vec![Vec::new()];
+    for (i, line) in input.lines().enumerate() {
+        match line {
+            "" => elves.push(Vec::new()),
             svalue => {
-                let v = svalue.parse::<u32>().unwrap();
-                calories += v;
+                let v = svalue.parse::<u32>().map_err(|e| AocError::Parse {
+                    line: i + 1,
+                    column: 1,
+                    message: format!("invalid calories '{svalue}' ({e})"),
+                })?;
+                elves.last_mut().unwrap().push(v);
             },
         }
     }
-    elves_calories.push(calories);
+    // Trailing or repeated empty lines do not separate elves
+    elves.retain(|calories| !calories.is_empty());
+    Ok(elves)
+}
+
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let elves = Day01::parse_file(filename)?;
+    Ok(calculate_part_1(&elves))
+}
+
+fn calculate_part_1(elves: &[Vec<u32>]) -> u32 {
+    elves.iter()
+        .map(|calories| calories.iter().sum())
+        .max()
+        .unwrap_or(0)
+}
+
+
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let elves = Day01::parse_file(filename)?;
+    calculate_part_2(&elves)
+}
+
+fn calculate_part_2(elves: &[Vec<u32>]) -> Result<u32> {
+    let mut elves_calories: Vec<u32> = elves.iter()
+        .map(|calories| calories.iter().sum())
+        .collect();
+    if elves_calories.len() < 3 {
+        return Err(AocError::invalid(format!("expected at least 3 elves, found {}", elves_calories.len())));
+    }
     elves_calories.sort_unstable_by(|a, b| {
         b.partial_cmp(a).unwrap()
     });
-    elves_calories.get(0).unwrap()
-        + elves_calories.get(1).unwrap()
-        + elves_calories.get(2).unwrap()
+    Ok(elves_calories[0..3].iter().sum())
 }
 
 
@@ -56,12 +85,29 @@ mod test {
 
     #[test]
     fn test_part1() {
-        assert_eq!(super::run_part_1("test_input/day01.txt"), 24000);
+        assert_eq!(super::run_part_1("test_input/day01.txt").unwrap(), 24000);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(super::run_part_2("test_input/day01.txt"), 45000);
+        assert_eq!(super::run_part_2("test_input/day01.txt").unwrap(), 45000);
+    }
+
+    #[test]
+    fn test_invalid_calories() {
+        assert_eq!(
+            super::parse_elves("100\n\n1O0\n").unwrap_err().to_string(),
+            "parse error at line 3, column 1: invalid calories '1O0' (invalid digit found in string)"
+        );
     }
-}
 
+    #[test]
+    fn test_blank_lines() {
+        let elves = super::parse_elves("\n100\n\n\n200\n300\n\n").unwrap();
+        assert_eq!(elves, vec![vec![100], vec![200, 300]]);
+        assert_eq!(
+            super::calculate_part_2(&elves).unwrap_err().to_string(),
+            "invalid input: expected at least 3 elves, found 2"
+        );
+    }
+}
@@ -1,11 +1,13 @@
 
-use std::{path::Path, fs::File};
-use std::io::{self, BufRead};
+use std::path::Path;
 
 use nom::IResult;
 use nom::character::complete::{char, one_of};
 use nom::sequence::separated_pair;
-use nom::combinator::map;
+
+use crate::error::{AocError, Result};
+use crate::parse;
+use crate::solution::{Answer, Solution};
 
 
 #[derive(Debug)]
@@ -16,24 +18,26 @@ enum Play {
 
 impl Play {
     
-    pub fn from_opponent(code: char) -> Self {
+    pub fn from_opponent(code: char) -> Result<Self> {
         match code {
-            'A' => Self::Rock,
-            'B' => Self::Paper,
-            'C' => Self::Scissor,
-            _ => panic!("Invalid value!"),
+            'A' => Ok(Self::Rock),
+            'B' => Ok(Self::Paper),
+            'C' => Ok(Self::Scissor),
+            _ => Err(AocError::invalid(format!("invalid opponent play '{code}'"))),
         }
     }
 
-    pub fn from_me(code: char) -> Self {
+    pub fn from_me(code: char) -> Result<Self> {
         match code {
-            'X' => Self::Rock,
-            'Y' => Self::Paper,
-            'Z' => Self::Scissor,
-            _ => panic!("Invalid value!"),
+            'X' => Ok(Self::Rock),
+            'Y' => Ok(Self::Paper),
+            'Z' => Ok(Self::Scissor),
+            _ => Err(AocError::invalid(format!("invalid play '{code}'"))),
         }
     }
 
+    // Scores are written as "shape + outcome" to mirror the puzzle rules.
+    #[allow(clippy::identity_op)]
     pub fn play_part_1(my_play: &Play, opponent_play: &Play) -> (PlayResult, u32) {
         match (my_play, opponent_play) {
             (Self::Rock, Self::Rock) => (PlayResult::Draw, 1 + 3),
@@ -48,6 +52,7 @@ impl Play {
         }
     }
 
+    #[allow(clippy::identity_op)]
     pub fn play_part_2(opponent_play: &Play, expected_result: &PlayResult) -> (Play, u32) {
         match (opponent_play, expected_result) {
             (Self::Rock, PlayResult::Won) => (Self::Paper, 2 + 6),
@@ -70,56 +75,82 @@ enum PlayResult {
 }
 
 impl PlayResult {
-    pub fn from_code_for_part_2(code: char) -> Self {
+    pub fn from_code_for_part_2(code: char) -> Result<Self> {
         match code {
-            'X' => Self::Lost,
-            'Y' => Self::Draw,
-            'Z' => Self::Won,
-            _ => panic!("Invalid value!"),
+            'X' => Ok(Self::Lost),
+            'Y' => Ok(Self::Draw),
+            'Z' => Ok(Self::Won),
+            _ => Err(AocError::invalid(format!("invalid expected result '{code}'"))),
         }
     }
 }
 
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u32 {
-    let file = File::open(filename).unwrap();
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    type Input = Vec<(char, char)>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_strategy(input)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_1(input)?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_2(input)?.into())
+    }
+}
+
+// Each round of the strategy guide is kept as its two codes: the meaning of the second one
+// depends on the part
+fn parse_strategy(input: &str) -> Result<Vec<(char, char)>> {
+    parse::each_line(input, parse_line)
+}
+
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let strategy = Day02::parse_file(filename)?;
+    calculate_part_1(&strategy)
+}
+
+fn calculate_part_1(strategy: &[(char, char)]) -> Result<u32> {
     let mut total_score = 0u32;
-    for line in io::BufReader::new(file).lines() {
-        let line = line.unwrap();
-        let (_, (opponent_play, my_play)) = parse_line_part_1(&line).unwrap();
+    for &(opponent_code, my_code) in strategy {
+        let opponent_play = Play::from_opponent(opponent_code)?;
+        let my_play = Play::from_me(my_code)?;
         let (_play_result, score) = Play::play_part_1(&my_play, &opponent_play);
         total_score += score;
     }
-    total_score
+    Ok(total_score)
 }
 
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> u32 {
-    let file = File::open(filename).unwrap();
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let strategy = Day02::parse_file(filename)?;
+    calculate_part_2(&strategy)
+}
+
+fn calculate_part_2(strategy: &[(char, char)]) -> Result<u32> {
     let mut total_score = 0u32;
-    for line in io::BufReader::new(file).lines() {
-        let line = line.unwrap();
-        let (_, (opponent_play, expected_outcome)) = parse_line_part_2(&line).unwrap();
+    for &(opponent_code, expected_code) in strategy {
+        let opponent_play = Play::from_opponent(opponent_code)?;
+        let expected_outcome = PlayResult::from_code_for_part_2(expected_code)?;
         let (_my_play, score) = Play::play_part_2(&opponent_play, &expected_outcome);
         total_score += score;
     }
-    total_score
+    Ok(total_score)
 }
 
 
-fn parse_line_part_1(input: &str) -> IResult<&str, (Play, Play)> {
-    separated_pair(
-        map(one_of("ABC"), |c| Play::from_opponent(c)),
-        char(' '),
-        map(one_of("XYZ"), |c| Play::from_me(c))
-    )(input)
-}
-
-fn parse_line_part_2(input: &str) -> IResult<&str, (Play, PlayResult)> {
+fn parse_line(input: &str) -> IResult<&str, (char, char)> {
     separated_pair(
-        map(one_of("ABC"), |c| Play::from_opponent(c)),
+        one_of("ABC"),
         char(' '),
-        map(one_of("XYZ"), |c| PlayResult::from_code_for_part_2(c))
+        one_of("XYZ")
     )(input)
 }
 
@@ -129,12 +160,20 @@ mod test {
 
     #[test]
     fn test_part1() {
-        assert_eq!(super::run_part_1("test_input/day02.txt"), 15);
+        assert_eq!(super::run_part_1("test_input/day02.txt").unwrap(), 15);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(super::run_part_2("test_input/day02.txt"), 12);
+        assert_eq!(super::run_part_2("test_input/day02.txt").unwrap(), 12);
+    }
+
+    #[test]
+    fn test_invalid_play() {
+        assert_eq!(
+            super::parse_strategy("A Y\nB Q\n").unwrap_err().to_string(),
+            "parse error at line 2, column 3: unexpected input 'Q' (OneOf)"
+        );
     }
 
 }
@@ -1,64 +1,104 @@
 
-use std::{path::Path, fs::File};
-use std::io::{self, BufRead, BufReader};
+use std::path::Path;
 
 use itertools::Itertools;
 
+use crate::error::{AocError, Result};
+use crate::solution::{Answer, Solution};
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u32 {
-    let file = File::open(filename).unwrap();
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    type Input = Vec<String>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_rucksacks(input)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_1(input)?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_2(input)?.into())
+    }
+}
+
+// Rucksacks are split in halves by byte index, so only ASCII items are accepted
+fn parse_rucksacks(input: &str) -> Result<Vec<String>> {
+    input.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            match line.find(|c: char| !c.is_ascii()) {
+                Some(position) => Err(AocError::Parse {
+                    line: i + 1,
+                    column: line[..position].chars().count() + 1,
+                    message: "rucksack contains non-ASCII items".to_string(),
+                }),
+                None => Ok(line.to_string()),
+            }
+        })
+        .collect()
+}
+
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let rucksacks = Day03::parse_file(filename)?;
+    calculate_part_1(&rucksacks)
+}
+
+fn calculate_part_1(rucksacks: &[String]) -> Result<u32> {
     let mut total_priority = 0u32;
-    for line in io::BufReader::new(file).lines() {
-        let line = line.unwrap();
+    for (i, line) in rucksacks.iter().enumerate() {
         let length = line.len();
         let (part1, part2) = (&line[0..length/2], &line[length/2..length]);
-        let common_item = find_common_item(part1, part2).unwrap();
-        let priority = get_item_priority(&common_item).unwrap();
+        let common_item = find_common_item(part1, part2)
+            .ok_or_else(|| AocError::invalid(format!("no common item in rucksack at line {}", i + 1)))?;
+        let priority = get_item_priority(&common_item)?;
         // println!("{:?} {:?}", common_item, priority);
         total_priority += priority;
     }
-    total_priority
+    Ok(total_priority)
 }
 
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> u32 {
-    let file = File::open(filename).unwrap();
-    BufReader::new(file)
-        .lines()
-        .map(|s| s.unwrap())
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let rucksacks = Day03::parse_file(filename)?;
+    calculate_part_2(&rucksacks)
+}
+
+fn calculate_part_2(rucksacks: &[String]) -> Result<u32> {
+    rucksacks
+        .iter()
         .tuples::<(_, _, _)>()
-        .map(|(s1, s2, s3)| find_common_item_part_2(s1, s2, s3).unwrap())
-        .map(|c| get_item_priority(&c).unwrap())
+        .enumerate()
+        .map(|(i, (s1, s2, s3))| {
+            let badge = find_common_item_part_2(s1, s2, s3)
+                .ok_or_else(|| AocError::invalid(format!("no badge for group at line {}", i * 3 + 1)))?;
+            get_item_priority(&badge)
+        })
         .sum()
 }
 
 
 fn find_common_item(s1: &str, s2: &str) -> Option<char> {
-    for c in s1.chars() {
-        if s2.contains(c) {
-            return Some(c);
-        }
-    }
-    None
+    s1.chars().find(|&c| s2.contains(c))
 }
 
-pub fn find_common_item_part_2(s1: String, s2: String, s3: String) -> Option<char> {
-    for c in s1.chars() {
-        if s2.contains(c) && s3.contains(c) {
-            return Some(c);
-        }
-    }
-    None
+pub fn find_common_item_part_2(s1: &str, s2: &str, s3: &str) -> Option<char> {
+    s1.chars().find(|&c| s2.contains(c) && s3.contains(c))
 }
 
-fn get_item_priority(item: &char) -> Result<u32, &str> {
+fn get_item_priority(item: &char) -> Result<u32> {
     if item.is_ascii_lowercase() {
         return Ok(*item as u32 - 'a' as u32 + 1);
     }
     else if item.is_ascii_uppercase() {
         return Ok(*item as u32 - 'A' as u32 + 27)
     }
-    Err("Invalid character!")
+    Err(AocError::invalid(format!("invalid item '{item}'")))
 }
 
 
@@ -67,12 +107,12 @@ mod test {
 
     #[test]
     fn test_part1() {
-        assert_eq!(super::run_part_1("test_input/day03.txt"), 157);
+        assert_eq!(super::run_part_1("test_input/day03.txt").unwrap(), 157);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(super::run_part_2("test_input/day03.txt"), 70);
+        assert_eq!(super::run_part_2("test_input/day03.txt").unwrap(), 70);
     }
 
 }
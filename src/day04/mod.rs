@@ -1,5 +1,4 @@
-use std::{fs::File, path::Path};
-use std::io::{self, BufRead};
+use std::path::Path;
 
 use std::ops::RangeInclusive;
 
@@ -8,37 +7,66 @@ use nom::character::complete::{char, u32};
 use nom::sequence::separated_pair;
 use nom::combinator::map;
 
+use crate::error::Result;
+use crate::interval;
+use crate::parse;
+use crate::solution::{Answer, Solution};
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u32 {
-    let file = File::open(filename).unwrap();
-    let mut count_contained_ranges = 0u32;
-    for line in io::BufReader::new(file).lines() {
-        let line = line.unwrap();
-        let (_, (r1, r2)) = parse_line(&line).unwrap();
-        if contains(&r1, &r2) || contains(&r2, &r1) {
-            count_contained_ranges += 1;
-        }
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    type Input = Vec<SectionPair>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_pairs(input)
     }
-    count_contained_ranges
-}
 
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_1(input).into())
+    }
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> u32 {
-    let file = File::open(filename).unwrap();
-    let mut count_overlapping_ranges = 0u32;
-    for line in io::BufReader::new(file).lines() {
-        let line = line.unwrap();
-        let (_, (r1, r2)) = parse_line(&line).unwrap();
-        if overlap(&r1, &r2) {
-            count_overlapping_ranges += 1;
-        }
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_2(input).into())
     }
-    count_overlapping_ranges
 }
 
 
+/// Section assignments of a pair of elves
+pub type SectionPair = (RangeInclusive<u32>, RangeInclusive<u32>);
+
+fn parse_pairs(input: &str) -> Result<Vec<SectionPair>> {
+    parse::each_line(input, parse_line)
+}
+
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let pairs = Day04::parse_file(filename)?;
+    Ok(calculate_part_1(&pairs))
+}
+
+fn calculate_part_1(pairs: &[SectionPair]) -> u32 {
+    pairs.iter()
+        .filter(|(r1, r2)| interval::contains(r1, r2) || interval::contains(r2, r1))
+        .count() as u32
+}
+
+
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let pairs = Day04::parse_file(filename)?;
+    Ok(calculate_part_2(&pairs))
+}
+
+fn calculate_part_2(pairs: &[SectionPair]) -> u32 {
+    pairs.iter()
+        .filter(|(r1, r2)| interval::overlaps(r1, r2))
+        .count() as u32
+}
+
 
-fn parse_line(input: &str) -> IResult<&str, (RangeInclusive<u32>, RangeInclusive<u32>)> {
+
+fn parse_line(input: &str) -> IResult<&str, SectionPair> {
     separated_pair(
         parse_range,
         char(','),
@@ -59,29 +87,17 @@ fn parse_range(input: &str) -> IResult<&str, RangeInclusive<u32>> {
 }
 
 
-fn contains(r1: &RangeInclusive<u32>, r2: &RangeInclusive<u32>) -> bool {
-    r1.start() <= r2.start() && r1.end() >= r2.end()
-}
-
-fn overlap(r1: &RangeInclusive<u32>, r2: &RangeInclusive<u32>) -> bool {
-    contains(r1, r2)
-    || contains(r2, r1)
-    || (r1.start() <= r2.start() && r1.end() >= r2.start())
-    || (r1.start() <= r2.end() && r1.end() >= r2.end())
-}
-
-
 #[cfg(test)]
 mod test {
 
     #[test]
     fn test_part1() {
-        assert_eq!(super::run_part_1("test_input/day04.txt"), 2);
+        assert_eq!(super::run_part_1("test_input/day04.txt").unwrap(), 2);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(super::run_part_2("test_input/day04.txt"), 4);
+        assert_eq!(super::run_part_2("test_input/day04.txt").unwrap(), 4);
     }
 
 }
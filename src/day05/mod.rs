@@ -1,4 +1,4 @@
-use std::{fs, path::Path};
+use std::path::Path;
 
 use nom::IResult;
 
@@ -9,79 +9,126 @@ use nom::branch::alt;
 use nom::combinator::{map, verify};
 use nom::multi::{many1, separated_list1};
 
+use crate::error::{AocError, Result};
+use crate::parse;
+use crate::solution::{Answer, Solution};
 
-struct Move {
+
+#[derive(Debug, Clone)]
+pub struct Move {
     count: usize,
     from_stack: usize,      // between 1 and N
     to_stack: usize,        // between 1 and N
 }
 
-struct SupplyStacks <const N: usize> {
+#[derive(Debug, Clone)]
+pub struct SupplyStacks <const N: usize> {
     stacks: [Vec<char>; N],
     moves: Vec<Move>,
 }
 
 impl <const N: usize> SupplyStacks<N> {
 
-    pub fn do_moves(stacks: &mut [Vec<char>; N], moves: &Vec<Move>) {
+    // Check that all moves refer to existing stacks
+    fn check_moves(&self) -> Result<()> {
+        for (i, m) in self.moves.iter().enumerate() {
+            if m.from_stack == 0 || m.from_stack > N || m.to_stack == 0 || m.to_stack > N {
+                return Err(AocError::invalid(format!("move {} refers to a stack outside 1..={N}", i + 1)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn do_moves(stacks: &mut [Vec<char>; N], moves: &Vec<Move>) -> Result<()> {
         for m in moves {
             for _ in 0..m.count {
-                let c = stacks[m.from_stack - 1].pop().unwrap();
+                let c = stacks[m.from_stack - 1].pop()
+                    .ok_or_else(|| AocError::invalid(format!("cannot move a crate from empty stack {}", m.from_stack)))?;
                 stacks[m.to_stack - 1].push(c);
             }
         }
+        Ok(())
     }
 
-    pub fn do_moves_part_2(stacks: &mut [Vec<char>; N], moves: &Vec<Move>) {
+    pub fn do_moves_part_2(stacks: &mut [Vec<char>; N], moves: &Vec<Move>) -> Result<()> {
         for m in moves {
-            assert!(m.from_stack > 0 && m.from_stack <= N);
-            assert!(m.to_stack > 0 && m.to_stack <= N);
-            assert!(m.from_stack != m.to_stack);
-            
-            let truncate_start_position = stacks[m.from_stack - 1].len() - m.count;
+            let truncate_start_position = stacks[m.from_stack - 1].len().checked_sub(m.count)
+                .ok_or_else(|| AocError::invalid(format!("cannot move {} crates from stack {}", m.count, m.from_stack)))?;
             let mut crates_to_move: Vec<_> = stacks[m.from_stack - 1].drain(truncate_start_position..).collect();
             // Note: Rust does not allow mutable borrowing of 2 elements of an array without unsafe
             // so we cannot drain directly from a stack to another as they are in the same array "stacks".
 
             stacks[m.to_stack - 1].append(&mut crates_to_move);
         }
+        Ok(())
     }
 
 
-    pub fn get_result(&self) -> String {
+    pub fn get_result(&self) -> Result<String> {
         let mut result = String::with_capacity(N);
-        for stack in &self.stacks {
-            result.push(*stack.last().unwrap());
+        for (i, stack) in self.stacks.iter().enumerate() {
+            let top = stack.last()
+                .ok_or_else(|| AocError::invalid(format!("stack {} is empty at the end", i + 1)))?;
+            result.push(*top);
         }
-        result
+        Ok(result)
     }
 }
 
 
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> String {
-    let input = fs::read_to_string(filename).unwrap();
-    let (_, mut game) = parse_all::<9>(&input).unwrap();
-    SupplyStacks::do_moves(&mut game.stacks, &game.moves);
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    type Input = SupplyStacks<9>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_game::<9>(input)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        let mut game = input.clone();
+        SupplyStacks::do_moves(&mut game.stacks, &game.moves)?;
+        Ok(game.get_result()?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        let mut game = input.clone();
+        SupplyStacks::do_moves_part_2(&mut game.stacks, &game.moves)?;
+        Ok(game.get_result()?.into())
+    }
+}
+
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<String> {
+    let mut game = Day05::parse_file(filename)?;
+    SupplyStacks::do_moves(&mut game.stacks, &game.moves)?;
     game.get_result()
 }
 
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> String {
-    let input = fs::read_to_string(filename).unwrap();
-    let (_, mut game) = parse_all::<9>(&input).unwrap();
-    SupplyStacks::do_moves_part_2(&mut game.stacks, &game.moves);
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<String> {
+    let mut game = Day05::parse_file(filename)?;
+    SupplyStacks::do_moves_part_2(&mut game.stacks, &game.moves)?;
     game.get_result()
 }
 
 
+fn parse_game<const N: usize>(input: &str) -> Result<SupplyStacks<N>> {
+    let game = parse::finish(input, parse_all::<N>)?;
+    game.check_moves()?;
+    Ok(game)
+}
+
+
 fn parse_crate(input: &str) -> IResult<&str, Option<char>> {
     alt((
         delimited(
             char('['),
             map(
                 satisfy(|c| c.is_ascii_uppercase()),
-                |c| Some(c)
+                Some
             ),
             char(']')
         ),
@@ -109,10 +156,7 @@ fn parse_crates<const N: usize>(input: &str) -> IResult<&str, [Vec<char>; N]> {
             let mut result: [Vec<char>; N] = std::array::from_fn(|_| Vec::new());
             for stack in v.iter().rev() {
                 for (i, crat) in stack.iter().enumerate() {
-                    match crat {
-                        Some(c) => result[i].push(*c),
-                        None => (),
-                    }
+                    if let Some(c) = crat { result[i].push(*c) }
                 }
             }
             result
@@ -194,17 +238,39 @@ mod test {
     #[test]
     fn test_part1() {
         let input = fs::read_to_string("test_input/day05.txt").unwrap();
-        let (_, mut game) = super::parse_all::<3>(&input).unwrap();
-        super::SupplyStacks::do_moves(&mut game.stacks, &game.moves);
-        assert_eq!(game.get_result(), String::from("CMZ"));
+        let mut game = super::parse_game::<3>(&input).unwrap();
+        super::SupplyStacks::do_moves(&mut game.stacks, &game.moves).unwrap();
+        assert_eq!(game.get_result().unwrap(), String::from("CMZ"));
     }
 
     #[test]
     fn test_part2() {
         let input = fs::read_to_string("test_input/day05.txt").unwrap();
-        let (_, mut game) = super::parse_all::<3>(&input).unwrap();
-        super::SupplyStacks::do_moves_part_2(&mut game.stacks, &game.moves);
-        assert_eq!(game.get_result(), String::from("MCD"));
+        let mut game = super::parse_game::<3>(&input).unwrap();
+        super::SupplyStacks::do_moves_part_2(&mut game.stacks, &game.moves).unwrap();
+        assert_eq!(game.get_result().unwrap(), String::from("MCD"));
+    }
+
+    #[test]
+    fn test_invalid_move() {
+        let input = fs::read_to_string("test_input/day05.txt").unwrap()
+            .replace("move 1 from 1 to 2", "move 1 from 4 to 2");
+        assert_eq!(
+            super::parse_game::<3>(&input).unwrap_err().to_string(),
+            "invalid input: move 4 refers to a stack outside 1..=3"
+        );
+    }
+
+    #[test]
+    fn test_move_to_same_stack() {
+        // Moving crates to the stack they come from changes nothing
+        let input = fs::read_to_string("test_input/day05.txt").unwrap() + "move 3 from 3 to 3\n";
+        let mut game = super::parse_game::<3>(&input).unwrap();
+        super::SupplyStacks::do_moves(&mut game.stacks, &game.moves).unwrap();
+        assert_eq!(game.get_result().unwrap(), String::from("CMZ"));
+        let mut game = super::parse_game::<3>(&input).unwrap();
+        super::SupplyStacks::do_moves_part_2(&mut game.stacks, &game.moves).unwrap();
+        assert_eq!(game.get_result().unwrap(), String::from("MCD"));
     }
 
 }
@@ -1,18 +1,57 @@
-use std::{fs, path::Path};
+use std::path::Path;
 use itertools::Itertools;
 
+use crate::error::{AocError, Result};
+use crate::solution::{Answer, Solution};
+
 
 const START_OF_MESSAGE_LENGTH: usize = 14;
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u32 {
-    let input = fs::read_to_string(filename).unwrap();
-    get_start_of_packet_marker_position(&input).unwrap() as u32
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    type Input = String;
+
+    // The datastream is a single line
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.trim_end().to_string())
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_1(input)?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_2(input)?.into())
+    }
+}
+
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let datastream = Day06::parse_file(filename)?;
+    calculate_part_1(&datastream)
+}
+
+fn calculate_part_1(input: &str) -> Result<u32> {
+    get_start_of_packet_marker_position(input)
+        .map(|position| position as u32)
+        .ok_or_else(|| AocError::invalid("no start-of-packet marker found"))
 }
 
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> u32 {
-    let input = fs::read_to_string(filename).unwrap();
-    get_start_of_message_marker_position(&input).unwrap() as u32
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let datastream = Day06::parse_file(filename)?;
+    calculate_part_2(&datastream)
+}
+
+fn calculate_part_2(input: &str) -> Result<u32> {
+    if !input.is_ascii() {
+        return Err(AocError::invalid("the datastream must only contain ASCII characters"));
+    }
+    get_start_of_message_marker_position(input)
+        .map(|position| position as u32)
+        .ok_or_else(|| AocError::invalid("no start-of-message marker found"))
 }
 
 
@@ -35,7 +74,7 @@ fn get_start_of_message_marker_position(input: &str) -> Option<usize> {
     let mut position = 0;
     while (position + START_OF_MESSAGE_LENGTH) < input.len() {
         let chunk_to_test = &input[position..position+14];
-        if let Some((i, _)) = find_repeated_characters(&chunk_to_test) {
+        if let Some((i, _)) = find_repeated_characters(chunk_to_test) {
             position += i + 1;
         } else {
             return Some(position + START_OF_MESSAGE_LENGTH);
@@ -1,8 +1,14 @@
 
-use std::{fs, path::Path};
+use std::fmt::Write;
+use std::iter;
+use std::path::Path;
 use id_tree::*;
 use id_tree::InsertBehavior::*;
 
+use crate::error::{AocError, Result};
+use crate::parse;
+use crate::solution::{Answer, Solution};
+
 const TOTAL_SIZE: u64 = 70_000_000;
 const NEEDED_SIZE: u64 = 30_000_000;
 
@@ -17,7 +23,7 @@ pub enum CdSpec<'a> {
 #[derive(Debug, PartialEq)]
 pub enum LsOutputRecord<'a> {
     Dir { name: &'a str },
-    File { name: &'a str, size: u32 }
+    File { name: &'a str, size: u64 }
 }
 
 #[derive(Debug, PartialEq)]
@@ -26,156 +32,390 @@ pub enum Command<'a> {
     Ls { result: Vec<LsOutputRecord<'a>> },
 }
 
-const SIZE_LIMIT: u32 = 100000;
+const SIZE_LIMIT: u64 = 100000;
+
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    type Input = FileSystem;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let cmds = parse_commands(input)?;
+        FileSystem::from_commands(&cmds)
+    }
 
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_1(input).into())
+    }
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u32 {
-    let input = fs::read_to_string(filename).unwrap();
-    let (_, input) = parser::parse_input(&input).unwrap();
-    calculate_part_1(input)
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_2(input)?.into())
+    }
 }
 
-fn calculate_part_1(cmds: Vec<Command>) -> u32 {
-    let mut stack_current_folders: Vec<(&str, u32)> = vec!(("/", 0));
-    let mut total_size = 0u32;
-    for cmd in cmds.iter().skip(1) {
-        match cmd {
-            Command::Cd{ spec: CdSpec::Folder{ name: nm } } => {
-                stack_current_folders.push((nm, 0));
-            }
-            Command::Cd{ spec: CdSpec::Root } => {
-                panic!("Should not happen!");
-            }
-            Command::Cd{ spec: CdSpec::Parent } => {
-                let this_folder = stack_current_folders.pop().unwrap();
-                if this_folder.1 <= SIZE_LIMIT {
-                    total_size += this_folder.1;
+
+// Parse the terminal output, which must start in the root folder
+fn parse_commands(input: &str) -> Result<Vec<Command<'_>>> {
+    let cmds = parse::finish(input, parser::parse_input)?;
+    if cmds.first() != Some(&Command::Cd{ spec: CdSpec::Root }) {
+        return Err(AocError::invalid("the terminal output must start with `cd /`"));
+    }
+    Ok(cmds)
+}
+
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u64> {
+    let fs = Day07::parse_file(filename)?;
+    Ok(calculate_part_1(&fs))
+}
+
+fn calculate_part_1(fs: &FileSystem) -> u64 {
+    fs.folders()
+        .map(Entry::size)
+        .filter(|&size| size <= SIZE_LIMIT)
+        .sum()
+}
+
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u64> {
+    let fs = Day07::parse_file(filename)?;
+    calculate_part_2(&fs)
+}
+
+fn calculate_part_2(fs: &FileSystem) -> Result<u64> {
+    let used_size = fs.root().size();
+    let free_space = TOTAL_SIZE.checked_sub(used_size)
+        .ok_or_else(|| AocError::invalid(format!("used size {used_size} is larger than the disk")))?;
+    let space_to_free = NEEDED_SIZE.saturating_sub(free_space);
+    let folder = find_folder_to_delete(fs, space_to_free)
+        .ok_or_else(|| AocError::invalid("no folder is large enough to free the needed space"))?;
+    Ok(folder.size())
+}
+
+fn find_folder_to_delete(fs: &FileSystem, space_to_free: u64) -> Option<&Entry> {
+    fs.folders()
+        .filter(|folder| folder.size() >= space_to_free)
+        .min_by_key(|folder| folder.size())
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Folder,
+    File,
+}
+
+/// A file or a folder of the filesystem. The size of a folder is the total size of the files
+/// it contains.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    name: String,
+    kind: EntryKind,
+    size: u64,
+}
+
+impl Entry {
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> EntryKind {
+        self.kind
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn is_folder(&self) -> bool {
+        self.kind == EntryKind::Folder
+    }
+}
+
+
+/// The files and folders seen during a terminal session.
+///
+/// A folder can be visited and listed several times, its entries are only recorded once.
+#[derive(Debug)]
+pub struct FileSystem {
+    tree: Tree<Entry>,
+}
+
+impl FileSystem {
+
+    pub fn from_commands(commands: &[Command]) -> Result<Self> {
+        let mut tree: Tree<Entry> = Tree::new();
+        let root = Entry { name: "/".to_string(), kind: EntryKind::Folder, size: 0 };
+        let root_id = tree.insert(Node::new(root), AsRoot).unwrap();
+        let mut fs = Self { tree };
+        let mut current_node_id = root_id.clone();
+
+        for cmd in commands {
+            match cmd {
+                Command::Cd{ spec: CdSpec::Folder{ name } } => {
+                    // The folder may not have been listed yet
+                    current_node_id = fs.add_entry(&current_node_id, name, EntryKind::Folder, 0)?;
                 }
-            }
-            Command::Ls{ result: ls_output } => {
-                for ls_record in ls_output {
-                    match ls_record {
-                        LsOutputRecord::Dir {name: _name} => {
-                            // Nothing to do
-                        }
-                        LsOutputRecord::File {name: _name, size} => {
-                            for i in 0..stack_current_folders.len() {
-                                stack_current_folders[i].1 += size;
+                Command::Cd{ spec: CdSpec::Root } => {
+                    current_node_id = root_id.clone();
+                }
+                Command::Cd{ spec: CdSpec::Parent } => {
+                    current_node_id = fs.tree.get(&current_node_id).unwrap().parent()
+                        .ok_or_else(|| AocError::invalid("`cd ..` cannot go above the root folder"))?
+                        .clone();
+                }
+                Command::Ls{ result: ls_output } => {
+                    for ls_record in ls_output {
+                        match ls_record {
+                            LsOutputRecord::Dir { name } => {
+                                fs.add_entry(&current_node_id, name, EntryKind::Folder, 0)?;
+                            }
+                            LsOutputRecord::File { name, size } => {
+                                fs.add_entry(&current_node_id, name, EntryKind::File, *size)?;
                             }
                         }
                     }
                 }
             }
         }
+
+        fs.update_folder_sizes();
+        Ok(fs)
+    }
+
+    pub fn root(&self) -> &Entry {
+        self.tree.get(self.tree.root_node_id().unwrap()).unwrap().data()
     }
 
-    for fld in stack_current_folders {
-        if fld.1 <= SIZE_LIMIT {
-            total_size += fld.1;
+    /// The entry at an absolute path such as `/a/e/i`, `/` being the root folder.
+    pub fn get(&self, path: &str) -> Option<&Entry> {
+        let id = self.lookup(path)?;
+        Some(self.tree.get(&id).unwrap().data())
+    }
+
+    /// All the folders, each one before the folders it contains.
+    pub fn folders(&self) -> impl Iterator<Item = &Entry> {
+        self.tree.traverse_pre_order(self.tree.root_node_id().unwrap()).unwrap()
+            .map(Node::data)
+            .filter(|entry| entry.is_folder())
+    }
+
+    fn lookup(&self, path: &str) -> Option<NodeId> {
+        let path = path.strip_prefix('/')?;
+        path.split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(self.tree.root_node_id().unwrap().clone(), |id, name| self.child(&id, name))
+    }
+
+    fn child(&self, parent_id: &NodeId, name: &str) -> Option<NodeId> {
+        self.tree.children_ids(parent_id).unwrap()
+            .find(|&id| self.tree.get(id).unwrap().data().name == name)
+            .cloned()
+    }
+
+    // Absolute path of an entry, to report errors
+    fn path(&self, id: &NodeId) -> String {
+        let mut names: Vec<&str> = iter::once(self.tree.get(id).unwrap())
+            .chain(self.tree.ancestors(id).unwrap())
+            .filter(|node| node.parent().is_some())
+            .map(|node| node.data().name.as_str())
+            .collect();
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    // Add an entry to a folder, unless it is already known. Returns the id of the entry.
+    fn add_entry(&mut self, parent_id: &NodeId, name: &str, kind: EntryKind, size: u64) -> Result<NodeId> {
+        let Some(id) = self.child(parent_id, name) else {
+            let entry = Entry { name: name.to_string(), kind, size };
+            return Ok(self.tree.insert(Node::new(entry), UnderNode(parent_id)).unwrap());
+        };
+        let known = self.tree.get(&id).unwrap().data();
+        if known.kind != kind {
+            return Err(AocError::invalid(format!("{} is both a file and a folder", self.path(&id))));
+        }
+        if kind == EntryKind::File && known.size != size {
+            return Err(AocError::invalid(format!(
+                "file {} is listed with the sizes {} and {size}", self.path(&id), known.size
+            )));
         }
+        Ok(id)
     }
 
-    total_size
+    // Set the size of the folders to the total size of their children, files are listed only once
+    // so this is done at the end
+    fn update_folder_sizes(&mut self) {
+        let root_id = self.tree.root_node_id().unwrap().clone();
+        for node_id in self.tree.traverse_post_order_ids(&root_id).unwrap() {
+            let node = self.tree.get(&node_id).unwrap();
+            if !node.data().is_folder() {
+                continue;
+            }
+            let size = node.children().iter()
+                .map(|child| self.tree.get(child).unwrap().data().size)
+                .sum();
+            self.tree.get_mut(&node_id).unwrap().data_mut().size = size;
+        }
+    }
 }
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> u64 {
-    let input = fs::read_to_string(filename).unwrap();
-    let (_, input) = parser::parse_input(&input).unwrap();
-    let tree = build_folder_tree(&input);
-    let used_size = tree.get(tree.root_node_id().unwrap())
-            .unwrap()
-            .data().size;
-    let free_space = TOTAL_SIZE - used_size;
-    let space_to_free = NEEDED_SIZE - free_space;
-    let folder = find_folder_to_delete(&tree, space_to_free);
-    folder.unwrap().size
+/// An entry of the filesystem with its absolute path, and its depth (0 for the root folder).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathEntry<'a> {
+    pub path: String,
+    pub depth: usize,
+    pub entry: &'a Entry,
 }
 
-#[derive(Debug)]
-struct Folder {
-    _name: String,
-    size: u64,
+/// Conditions on the entries returned by `FileSystem::find`, all of them have to be met.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FindQuery {
+    /// Pattern of the name, where `*` matches any characters and `?` a single one
+    pub name: Option<String>,
+    pub kind: Option<EntryKind>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub min_depth: Option<usize>,
+    pub max_depth: Option<usize>,
 }
 
-fn build_folder_tree(commands: &Vec<Command>) -> Tree<Folder> {
-    let mut tree: Tree<Folder> = Tree::new();
-    let root_id = tree.insert(
-        Node::new(Folder { _name: "/".to_string(), size: 0, }),
-        AsRoot
-    ).unwrap();
-    let mut current_node_id = root_id.clone();
-
-    for cmd in commands.iter().skip(1) {
-        match cmd {
-            Command::Cd{ spec: CdSpec::Folder{ name: nm } } => {
-                current_node_id = tree.insert(
-                    Node::new(Folder { _name: nm.to_string(), size: 0, }),
-                    UnderNode(&current_node_id))
-                    .unwrap();
-            }
-            Command::Cd{ spec: CdSpec::Root } => {
-                panic!("Should never happen!");
-            }
-            Command::Cd{ spec: CdSpec::Parent } => {
-                current_node_id = tree.get(&current_node_id).unwrap().parent().unwrap().clone();
-            }
-            Command::Ls{ result: ls_output } => {
-                let current_folder_mut = tree.get_mut(&current_node_id).unwrap().data_mut();
-                for ls_record in ls_output {
-                    match ls_record {
-                        LsOutputRecord::Dir {name: _name} => {
-                            // Nothing to do
-                        }
-                        LsOutputRecord::File {name: _name, size} => {
-                            current_folder_mut.size += *size as u64;    // ??? why do we need to dereference *size ??? this is not a reference!
-                        }
-                    }
-                }
-            }
-        }
-    }
+impl FindQuery {
 
-    // Update parent folders with the size of their children
-    update_folder_size(&mut tree, &root_id);
+    pub fn matches(&self, found: &PathEntry) -> bool {
+        let entry = found.entry;
+        self.name.as_ref().is_none_or(|pattern| glob_matches(pattern, &entry.name))
+            && self.kind.is_none_or(|kind| entry.kind == kind)
+            && self.min_size.is_none_or(|size| entry.size >= size)
+            && self.max_size.is_none_or(|size| entry.size <= size)
+            && self.min_depth.is_none_or(|depth| found.depth >= depth)
+            && self.max_depth.is_none_or(|depth| found.depth <= depth)
+    }
+}
 
-    tree
+// Match a whole name against a pattern of `*` and `?`, going back to the last `*` on a mismatch
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` in the pattern, and of the name when it was reached
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // The `*` takes one more character
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
-#[allow(dead_code)]
-fn print_tree(tree: &Tree<Folder>) {
-    let mut s = String::new();
-    tree.write_formatted(&mut s).unwrap();
-    println!("{s}");
+/// A size in the style of `du -h`: in powers of 1024, rounded up to one decimal below 10 and to
+/// an integer above.
+pub fn human_size(size: u64) -> String {
+    const UNITS: [char; 6] = ['K', 'M', 'G', 'T', 'P', 'E'];
+    if size < 1024 {
+        return size.to_string();
+    }
+    let mut unit = 0;
+    while unit + 1 < UNITS.len() && size >> (10 * (unit + 1)) >= 1024 {
+        unit += 1;
+    }
+    let scale = 1u128 << (10 * (unit + 1));
+    let tenths = (size as u128 * 10).div_ceil(scale);
+    if tenths < 100 {
+        return format!("{}.{}{}", tenths / 10, tenths % 10, UNITS[unit]);
+    }
+    match (size as u128).div_ceil(scale) {
+        // Rounding up reached the next unit
+        1024 if unit + 1 < UNITS.len() => format!("1.0{}", UNITS[unit + 1]),
+        whole => format!("{whole}{}", UNITS[unit]),
+    }
 }
 
+impl FileSystem {
+
+    /// All the entries, each folder followed by its content sorted by name.
+    pub fn walk(&self) -> Vec<PathEntry<'_>> {
+        let mut entries = Vec::new();
+        self.walk_from(self.tree.root_node_id().unwrap(), "/".to_string(), 0, &mut entries);
+        entries
+    }
 
-// Update the size of all folders by adding the size of all their children
-fn update_folder_size(tree: &mut Tree<Folder>, root_id: &NodeId) {
-    for node_id in tree.traverse_post_order_ids(root_id).unwrap() {
-        let mut size_children = 0u64;
-        let node = tree.get(&node_id).unwrap();
-        for child in node.children() {
-            let child_size = tree.get(child).unwrap().data().size;
-            size_children += child_size;
+    fn walk_from<'a>(&'a self, id: &NodeId, path: String, depth: usize, entries: &mut Vec<PathEntry<'a>>) {
+        entries.push(PathEntry { path: path.clone(), depth, entry: self.tree.get(id).unwrap().data() });
+        for child_id in self.sorted_children(id) {
+            let name = &self.tree.get(child_id).unwrap().data().name;
+            let child_path = format!("{}/{name}", path.trim_end_matches('/'));
+            self.walk_from(child_id, child_path, depth + 1, entries);
         }
+    }
 
-        let node = tree.get_mut(&node_id).unwrap();
-        let data = node.data_mut();
-        data.size += size_children;
+    /// The entries which match the query, in the order of `walk`.
+    pub fn find(&self, query: &FindQuery) -> Vec<PathEntry<'_>> {
+        self.walk().into_iter().filter(|found| query.matches(found)).collect()
+    }
+
+    /// The size of every folder followed by its path, the content of a folder before it, as
+    /// `du` does.
+    pub fn du(&self, human: bool) -> String {
+        let mut s = String::new();
+        self.du_from(self.tree.root_node_id().unwrap(), "/", human, &mut s);
+        s
+    }
+
+    fn du_from(&self, id: &NodeId, path: &str, human: bool, s: &mut String) {
+        for child_id in self.sorted_children(id) {
+            let child = self.tree.get(child_id).unwrap().data();
+            if child.is_folder() {
+                let child_path = format!("{}/{}", path.trim_end_matches('/'), child.name);
+                self.du_from(child_id, &child_path, human, s);
+            }
+        }
+        let size = self.tree.get(id).unwrap().data().size;
+        let size = if human { human_size(size) } else { size.to_string() };
+        writeln!(s, "{size}\t{path}").unwrap();
+    }
+
+    /// The entries drawn as a tree with their sizes, the names of the folders ending with `/`.
+    pub fn tree(&self, human: bool) -> String {
+        let mut s = String::new();
+        self.tree_from(self.tree.root_node_id().unwrap(), "", "", human, &mut s);
+        s
     }
-}
 
-fn find_folder_to_delete(tree: &Tree<Folder>, space_to_free: u64) -> Option<&Folder> {
-    let root_id = (*tree).root_node_id().unwrap();
-    let mut current_size_found = TOTAL_SIZE;
-    let mut folder_found: Option<&Folder> = None;
-    for node in tree.traverse_post_order(&root_id).unwrap() {
-        if (node.data().size >= space_to_free) && (node.data().size < current_size_found) {
-            current_size_found = node.data().size;
-            folder_found = Some(&node.data());
+    // `prefix` comes before the entry, and `indent` before its content
+    fn tree_from(&self, id: &NodeId, prefix: &str, indent: &str, human: bool, s: &mut String) {
+        let entry = self.tree.get(id).unwrap().data();
+        let name = if entry.is_folder() && entry.name != "/" { format!("{}/", entry.name) } else { entry.name.clone() };
+        let size = if human { human_size(entry.size) } else { entry.size.to_string() };
+        writeln!(s, "{prefix}{name} ({size})").unwrap();
+
+        let children = self.sorted_children(id);
+        for (i, child_id) in children.iter().enumerate() {
+            if i + 1 < children.len() {
+                self.tree_from(child_id, &format!("{indent}├── "), &format!("{indent}│   "), human, s);
+            } else {
+                self.tree_from(child_id, &format!("{indent}└── "), &format!("{indent}    "), human, s);
+            }
         }
     }
-    folder_found
+
+    fn sorted_children(&self, id: &NodeId) -> Vec<&NodeId> {
+        let mut children: Vec<&NodeId> = self.tree.children_ids(id).unwrap().collect();
+        children.sort_by_key(|&child| &self.tree.get(child).unwrap().data().name);
+        children
+    }
 }
 
 
@@ -184,74 +424,63 @@ mod parser {
     use super::{CdSpec, LsOutputRecord, Command};
 
     use nom::IResult;
-    use nom::bytes::complete::{tag, take_while1};
-    use nom::character::complete::{char, u32, line_ending};
-    use nom::sequence::{preceded, terminated, delimited, separated_pair};
-    use nom::multi::many1;
+    use nom::bytes::complete::{tag, take_till1};
+    use nom::character::complete::{char, u64, line_ending};
+    use nom::sequence::{preceded, terminated, separated_pair};
+    use nom::multi::{many0, many1};
     use nom::branch::alt;
     use nom::combinator::map;
 
-    fn is_alphabetic_lowercase(c: char) -> bool {
-        c >= 'a' && c <= 'z'
-    }
-
-    fn is_alphabetic_lowercase_or_dot(c: char) -> bool {
-        (c >= 'a' && c <= 'z') || c == '.'
-    }
-
-    fn parse_folder_name(input: &str) -> IResult<&str, &str> {
-        take_while1(is_alphabetic_lowercase)(input)
-    }
-
-    fn parse_file_name(input: &str) -> IResult<&str, &str> {
-        // TODO: Improve this
-        take_while1(is_alphabetic_lowercase_or_dot)(input)
+    // Anything up to the end of the line, but '/' which separates the folders of a path
+    fn parse_name(input: &str) -> IResult<&str, &str> {
+        take_till1(|c| c == '\n' || c == '\r' || c == '/')(input)
     }
 
-    fn parse_cd_command(input: &str) -> IResult<&str, Command> {
-        delimited(
+    fn parse_cd_command(input: &str) -> IResult<&str, Command<'_>> {
+        preceded(
             tag("$ cd "),
             map(
+                // The line ending is part of each choice so that a name like `..a` is not taken for `..`
                 alt((
-                    map(tag("/"), |_| CdSpec::Root),
-                    map(tag(".."), |_| CdSpec::Parent),
-                    map(parse_folder_name, |f| CdSpec::Folder{ name: f} ),
+                    map(terminated(tag("/"), line_ending), |_| CdSpec::Root),
+                    map(terminated(tag(".."), line_ending), |_| CdSpec::Parent),
+                    map(terminated(parse_name, line_ending), |f| CdSpec::Folder{ name: f} ),
                 )),
                 |cd_spec| Command::Cd{ spec: cd_spec }
-            ),
-            line_ending
+            )
         )(input)
     }
 
-    fn parse_ls_output_line_dir(input: &str) -> IResult<&str, LsOutputRecord> {
+    fn parse_ls_output_line_dir(input: &str) -> IResult<&str, LsOutputRecord<'_>> {
         map(
             preceded(
                 tag("dir "),
-                parse_folder_name
+                parse_name
             ),
             |s| LsOutputRecord::Dir{name: s}
         )(input)
     }
 
-    fn parse_ls_output_line_file(input: &str) -> IResult<&str, LsOutputRecord> {
+    fn parse_ls_output_line_file(input: &str) -> IResult<&str, LsOutputRecord<'_>> {
         map(
             separated_pair(
-                u32,
+                u64,
                 char(' '),
-                parse_file_name,
+                parse_name,
             ),
-            |(size, name)| LsOutputRecord::File{ name: name, size: size}
+            |(size, name)| LsOutputRecord::File{ name, size}
         )(input)
     }
 
-    fn parse_ls_command(input: &str) -> IResult<&str, Command> {
+    fn parse_ls_command(input: &str) -> IResult<&str, Command<'_>> {
         preceded(
             terminated(
                 tag("$ ls"),
                 line_ending
             ),
+            // An empty folder lists nothing
             map(
-                many1(
+                many0(
                     terminated(
                         alt((
                             parse_ls_output_line_dir,
@@ -265,7 +494,7 @@ mod parser {
         )(input)
     }
 
-    pub fn parse_input(input: &str) -> IResult<&str, Vec<Command>> {
+    pub fn parse_input(input: &str) -> IResult<&str, Vec<Command<'_>>> {
         many1(
             alt((
                 parse_cd_command,
@@ -284,6 +513,9 @@ mod parser {
             assert_eq!(super::parse_cd_command("$ cd ..\n"), Ok(("", Command::Cd { spec: CdSpec::Parent } )));
             assert_eq!(super::parse_cd_command("$ cd /\n"), Ok(("", Command::Cd { spec: CdSpec::Root } )));
             assert_eq!(super::parse_cd_command("$ cd folder\n"), Ok(("", Command::Cd { spec: CdSpec::Folder { name: "folder" } } )));
+            assert_eq!(super::parse_cd_command("$ cd ..a\n"), Ok(("", Command::Cd { spec: CdSpec::Folder { name: "..a" } } )));
+            assert_eq!(super::parse_cd_command("$ cd My Files-2\r\n"), Ok(("", Command::Cd { spec: CdSpec::Folder { name: "My Files-2" } } )));
+            assert!(super::parse_cd_command("$ cd a/b\n").is_err());
         }
 
         #[test]
@@ -301,6 +533,21 @@ mod parser {
                     }
                 ))
             );
+            assert_eq!(
+                super::parse_ls_command("$ ls\ndir Old_Photos 2019\n5000000000 IMG-0001.JPG\n"),
+                Ok(("",
+                    Command::Ls {
+                        result: vec!(
+                            LsOutputRecord::Dir { name: "Old_Photos 2019" },
+                            LsOutputRecord::File { name: "IMG-0001.JPG", size: 5000000000 },
+                        )
+                    }
+                ))
+            );
+            assert_eq!(super::parse_ls_command("$ ls
+$ cd ..
+"), Ok(("$ cd ..
+", Command::Ls { result: vec!() })));
         }
 
     }
@@ -311,14 +558,147 @@ mod parser {
 #[cfg(test)]
 mod test {
 
+    use super::{Day07, EntryKind, FileSystem, FindQuery};
+    use crate::solution::Solution;
+
     #[test]
     fn test_part1() {
-        assert_eq!(super::run_part_1("test_input/day07.txt"), 95437);
+        assert_eq!(super::run_part_1("test_input/day07.txt").unwrap(), 95437);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(super::run_part_2("test_input/day07.txt"), 24933642);
+        assert_eq!(super::run_part_2("test_input/day07.txt").unwrap(), 24933642);
+    }
+
+    #[test]
+    fn test_invalid_cd_parent() {
+        let cmds = super::parse_commands("$ cd /\n$ cd ..\n").unwrap();
+        assert_eq!(
+            FileSystem::from_commands(&cmds).unwrap_err().to_string(),
+            "invalid input: `cd ..` cannot go above the root folder"
+        );
+    }
+
+    #[test]
+    fn test_filesystem() {
+        let fs = Day07::parse_file("test_input/day07.txt").unwrap();
+        assert_eq!(fs.get("/").unwrap().size(), 48381165);
+        assert_eq!(fs.get("/a/e").unwrap().size(), 584);
+        assert_eq!(fs.get("/a/e/i").unwrap().kind(), EntryKind::File);
+        assert_eq!(fs.get("/d/d.log").unwrap().size(), 8033020);
+        assert!(fs.get("/a/x").is_none());
+        assert!(fs.get("a").is_none());
+        assert_eq!(fs.folders().map(|folder| folder.name()).collect::<Vec<_>>(), vec!["/", "a", "e", "d"]);
+    }
+
+    #[test]
+    fn test_empty_folder() {
+        let fs = Day07::parse("$ cd /\n$ ls\ndir a\n10 b\n$ cd a\n$ ls\n$ cd ..\n").unwrap();
+        let a = fs.get("/a").unwrap();
+        assert_eq!((a.kind(), a.size()), (EntryKind::Folder, 0));
+        assert_eq!(fs.get("/").unwrap().size(), 10);
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(super::human_size(0), "0");
+        assert_eq!(super::human_size(1023), "1023");
+        assert_eq!(super::human_size(1024), "1.0K");
+        assert_eq!(super::human_size(1025), "1.1K");
+        assert_eq!(super::human_size(94853), "93K");
+        assert_eq!(super::human_size(48381165), "47M");
+        assert_eq!(super::human_size(1024 * 1024 - 1), "1.0M");
+        assert_eq!(super::human_size(u64::MAX), "16E");
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(super::glob_matches("*.log", "d.log"));
+        assert!(super::glob_matches("?", "e"));
+        assert!(super::glob_matches("a*b*c", "aXbYbZc"));
+        assert!(super::glob_matches("*", ""));
+        assert!(!super::glob_matches("*.log", "d.log.old"));
+        assert!(!super::glob_matches("??", "e"));
+    }
+
+    #[test]
+    fn test_du_tree() {
+        let fs = Day07::parse_file("test_input/day07.txt").unwrap();
+        assert_eq!(fs.du(false), "584\t/a/e\n94853\t/a\n24933642\t/d\n48381165\t/\n");
+        assert_eq!(fs.du(true), "584\t/a/e\n93K\t/a\n24M\t/d\n47M\t/\n");
+        assert_eq!(fs.tree(false), concat!(
+            "/ (48381165)\n",
+            "├── a/ (94853)\n",
+            "│   ├── e/ (584)\n",
+            "│   │   └── i (584)\n",
+            "│   ├── f (29116)\n",
+            "│   ├── g (2557)\n",
+            "│   └── h.lst (62596)\n",
+            "├── b.txt (14848514)\n",
+            "├── c.dat (8504156)\n",
+            "└── d/ (24933642)\n",
+            "    ├── d.ext (5626152)\n",
+            "    ├── d.log (8033020)\n",
+            "    ├── j (4060174)\n",
+            "    └── k (7214296)\n",
+        ));
+    }
+
+    #[test]
+    fn test_find() {
+        let fs = Day07::parse_file("test_input/day07.txt").unwrap();
+        let paths = |query: FindQuery| -> Vec<String> {
+            fs.find(&query).into_iter().map(|found| found.path).collect()
+        };
+        assert_eq!(fs.walk().len(), 14);
+        assert_eq!(paths(FindQuery { name: Some("d*".to_string()), ..FindQuery::default() }), vec!["/d", "/d/d.ext", "/d/d.log"]);
+        assert_eq!(
+            paths(FindQuery { kind: Some(EntryKind::Folder), max_size: Some(100000), ..FindQuery::default() }),
+            vec!["/a", "/a/e"]
+        );
+        assert_eq!(
+            paths(FindQuery { min_size: Some(8000000), min_depth: Some(2), ..FindQuery::default() }),
+            vec!["/d/d.log"]
+        );
+        assert_eq!(paths(FindQuery { max_depth: Some(0), ..FindQuery::default() }), vec!["/"]);
+    }
+
+    #[test]
+    fn test_revisited_folders() {
+        let fs = Day07::parse(concat!(
+            "$ cd /\n",
+            "$ cd a\n",
+            "$ ls\n",
+            "10 f\n",
+            "$ cd /\n",
+            "$ ls\n",
+            "dir a\n",
+            "5 g\n",
+            "$ cd a\n",
+            "$ ls\n",
+            "10 f\n",
+            "dir b\n",
+            "$ cd b\n",
+            "$ cd ..\n",
+            "$ cd ..\n",
+            "$ ls\n",
+            "dir a\n",
+            "5 g\n",
+        )).unwrap();
+        assert_eq!(fs.root().size(), 15);
+        assert_eq!(fs.get("/a").unwrap().size(), 10);
+        assert!(fs.get("/a/b").unwrap().is_folder());
+        assert_eq!(fs.folders().count(), 3);
+
+        assert_eq!(
+            Day07::parse("$ cd /\n$ ls\n1 f\n$ ls\n2 f\n").unwrap_err().to_string(),
+            "invalid input: file /f is listed with the sizes 1 and 2"
+        );
+        assert_eq!(
+            Day07::parse("$ cd /\n$ ls\n1 f\n$ cd f\n").unwrap_err().to_string(),
+            "invalid input: /f is both a file and a folder"
+        );
     }
 
 }
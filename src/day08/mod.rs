@@ -1,115 +1,118 @@
+use std::{fs, path::Path};
 
-use std::{fs::File, path::Path};
-use std::io::{BufRead, BufReader};
-use std::cmp::max;
+use crate::error::Result;
+use crate::geometry::Direction;
+use crate::grid::Grid;
+use crate::solution::{Answer, Solution};
 
-struct Forest<const SIZE: usize> {
-    trees: [[u8; SIZE]; SIZE],
+pub struct Forest {
+    trees: Grid<u8>,
 }
 
-impl<const SIZE: usize> Forest<SIZE> {
+impl Forest {
     
-    pub fn load<P: AsRef<Path>>(filename: P) -> Self {
-        let mut forest = [[0u8; SIZE]; SIZE];
-        let mut row = 0usize;
-        let file = File::open(filename).unwrap();
-        for line in BufReader::new(file).lines() {
-            let mut col = 0usize;
-            for c in line.unwrap().bytes() {
-                forest[row][col] = c - 48;
-                col += 1;
-            }
-            row += 1;
-        }
-        Self{trees: forest}
+    pub fn load<P: AsRef<Path>>(filename: P) -> Result<Self> {
+        let input = fs::read_to_string(filename)?;
+        Self::parse(&input)
     }
 
-    fn is_tree_visible(&self, tree_x: usize, tree_y: usize) -> bool {
-        assert!((tree_x < SIZE) && (tree_y < SIZE));
-
-        if (tree_x == 0) || (tree_x == SIZE - 1) || (tree_y == 0) || (tree_y == SIZE - 1) { return true; }
-
-        let tree_size = self.trees[tree_x][tree_y];
+    // The size of the forest is given by the input, all the rows must have the same length
+    pub fn parse(input: &str) -> Result<Self> {
+        let trees = Grid::parse(input, |_, c| {
+            c.to_digit(10)
+                .map(|height| height as u8)
+                .ok_or_else(|| format!("invalid tree height '{c}'"))
+        })?;
+        Ok(Self { trees })
+    }
 
-        if (0..tree_x).all(|x| self.trees[x][tree_y] < tree_size) { return true };          // left
-        if ((tree_x + 1)..SIZE).all(|x| self.trees[x][tree_y] < tree_size) { return true }; // right
-        if (0..tree_y).all(|y| self.trees[tree_x][y] < tree_size) { return true };          // top
-        if ((tree_y + 1)..SIZE).all(|y| self.trees[tree_x][y] < tree_size) { return true }; // bottom
-        false
+    // Trees on the edge are always visible since there is nothing between them and the outside
+    fn is_tree_visible(&self, tree_x: usize, tree_y: usize) -> bool {
+        let tree_size = self.trees[(tree_x, tree_y)];
+        Direction::ALL.into_iter().any(|direction| {
+            self.trees.ray((tree_x, tree_y), direction.offset()).all(|p| self.trees[p] < tree_size)
+        })
     }
 
     pub fn count_visible_trees(&self) -> u32 {
-        let mut visible_trees = (SIZE * 2 + (SIZE - 2) * 2) as u32;
-        for x in 1..SIZE-1 {
-            for y in 1..SIZE-1 {
-                if self.is_tree_visible(x, y) { visible_trees += 1; }
-            }
-        }
-        visible_trees
+        self.trees.positions()
+            .filter(|&(x, y)| self.is_tree_visible(x, y))
+            .count() as u32
     }
 
     pub fn calculate_scenic_score(&self, tree_x: usize, tree_y: usize) -> u32 {
-        assert!((tree_x < SIZE) && (tree_y < SIZE));
-        
-        if (tree_x == 0) || (tree_x == SIZE - 1) || (tree_y == 0) || (tree_y == SIZE - 1) { return 0; }
+        let tree_size = self.trees[(tree_x, tree_y)];
 
-        let tree_size = self.trees[tree_y][tree_x];
-
-        let scenic_score = self.count_visible_trees_up(tree_x, tree_y, tree_size)
+        self.count_visible_trees_up(tree_x, tree_y, tree_size)
             * self.count_visible_trees_left(tree_x, tree_y, tree_size)
             * self.count_visible_trees_right(tree_x, tree_y, tree_size)
-            * self.count_visible_trees_down(tree_x, tree_y, tree_size);
+            * self.count_visible_trees_down(tree_x, tree_y, tree_size)
+    }
 
-        scenic_score
+    // Count the trees seen in a direction, up to the first one at least as tall (included)
+    fn viewing_distance(&self, tree_x: usize, tree_y: usize, tree_size: u8, direction: Direction) -> u32 {
+        let mut distance = 0u32;
+        for p in self.trees.ray((tree_x, tree_y), direction.offset()) {
+            distance += 1;
+            if self.trees[p] >= tree_size {
+                break;
+            }
+        }
+        distance
     }
 
     fn count_visible_trees_up(&self, tree_x: usize, tree_y: usize, tree_size: u8) -> u32 {
-        match (0..tree_y).rev().position(|y| self.trees[y][tree_x] >= tree_size) {
-            None => tree_y as u32,
-            Some(y) => y as u32 + 1,
-        }
+        self.viewing_distance(tree_x, tree_y, tree_size, Direction::Up)
     }
 
     fn count_visible_trees_left(&self, tree_x: usize, tree_y: usize, tree_size: u8) -> u32 {
-        match (0..tree_x).rev().position(|x| self.trees[tree_y][x] >= tree_size) {
-            None => tree_x as u32,
-            Some(pos) => pos as u32 + 1,
-        }
+        self.viewing_distance(tree_x, tree_y, tree_size, Direction::Left)
     }
 
     fn count_visible_trees_right(&self, tree_x: usize, tree_y: usize, tree_size: u8) -> u32 {
-        match (tree_x+1..SIZE).position(|x| self.trees[tree_y][x] >= tree_size) {
-            None => (SIZE - tree_x - 1) as u32,
-            Some(pos) => pos as u32 + 1,
-        }
+        self.viewing_distance(tree_x, tree_y, tree_size, Direction::Right)
     }
 
     fn count_visible_trees_down(&self, tree_x: usize, tree_y: usize, tree_size: u8) -> u32 {
-        match (tree_y+1..SIZE).position(|y| self.trees[y][tree_x] >= tree_size) {
-            None => (SIZE - tree_y -1) as u32,
-            Some(pos) => pos as u32 + 1,
-        }
+        self.viewing_distance(tree_x, tree_y, tree_size, Direction::Down)
     }
 
     pub fn find_max_scenic_score(&self) -> u32 {
-        let mut max_scenic_score = 0u32;
-        for y in 1..SIZE-1 {
-            for x in 1..SIZE-1 {
-                max_scenic_score = max(max_scenic_score, self.calculate_scenic_score(x, y))
-            }
-        }
-        max_scenic_score
+        self.trees.positions()
+            .map(|(x, y)| self.calculate_scenic_score(x, y))
+            .max()
+            .unwrap_or(0)
     }
 }
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u32 {
-    let forest = Forest::<99>::load(filename);
-    forest.count_visible_trees()
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+    type Input = Forest;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Forest::parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(input.count_visible_trees().into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(input.find_max_scenic_score().into())
+    }
 }
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> u32 {
-    let forest = Forest::<99>::load(filename);
-    forest.find_max_scenic_score()
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let forest = Forest::load(filename)?;
+    Ok(forest.count_visible_trees())
+}
+
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let forest = Forest::load(filename)?;
+    Ok(forest.find_max_scenic_score())
 }
 
 #[cfg(test)]
@@ -117,19 +120,19 @@ mod test {
 
     #[test]
     fn test_part1() {
-        let forest = super::Forest::<5>::load("test_input/day08.txt");
+        let forest = super::Forest::load("test_input/day08.txt").unwrap();
         assert_eq!(forest.count_visible_trees(), 21);
     }
 
     #[test]
     fn test_part2() {
-        let forest = super::Forest::<5>::load("test_input/day08.txt");
+        let forest = super::Forest::load("test_input/day08.txt").unwrap();
         assert_eq!(forest.find_max_scenic_score(), 8);
     }
 
     #[test]
     fn test_calculate_scenic_score() {
-        let forest = super::Forest::<5>::load("test_input/day08.txt");
+        let forest = super::Forest::load("test_input/day08.txt").unwrap();
         assert_eq!(forest.count_visible_trees_up(2, 1, 5), 1);
         assert_eq!(forest.count_visible_trees_left(2, 1, 5), 1);
         assert_eq!(forest.count_visible_trees_right(2, 1, 5), 2);
@@ -139,7 +142,7 @@ mod test {
 
     #[test]
     fn test_calculate_scenic_score_2() {
-        let forest = super::Forest::<5>::load("test_input/day08.txt");
+        let forest = super::Forest::load("test_input/day08.txt").unwrap();
         assert_eq!(forest.count_visible_trees_up(2, 3, 5), 2);
         assert_eq!(forest.count_visible_trees_left(2, 3, 5), 2);
         assert_eq!(forest.count_visible_trees_right(2, 3, 5), 2);
@@ -147,6 +150,21 @@ mod test {
         assert_eq!(forest.calculate_scenic_score(2, 3), 8);
     }
 
-}
+    #[test]
+    fn test_rectangular_forest() {
+        let forest = super::Forest::parse("30373\n25512\n65332\n").unwrap();
+        assert_eq!(forest.count_visible_trees(), 14);
+        assert_eq!(forest.find_max_scenic_score(), 2);
+    }
 
+    #[test]
+    fn test_invalid_forest() {
+        assert!(super::Forest::parse("30373\n2551\n").is_err());
+        assert!(super::Forest::parse("").is_err());
+        assert_eq!(
+            super::Forest::parse("303\n2x5\n653\n").err().unwrap().to_string(),
+            "parse error at line 2, column 2: invalid tree height 'x'"
+        );
+    }
 
+}
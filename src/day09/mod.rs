@@ -1,37 +1,17 @@
-use std::hash::Hash;
-use std::{fs::File, path::Path};
-use std::io::{BufReader, BufRead};
+use std::path::Path;
 use std::collections::HashSet;
-use std::cmp::{min, max};
 
 use nom::IResult;
 use nom::sequence::separated_pair;
 use nom::character::complete::{char, u32, one_of};
-use nom::combinator::map;
-
-enum Direction {
-    Up,
-    Left,
-    Right,
-    Down,
-}
-
-impl TryFrom<char> for Direction {
-    type Error = &'static str;
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        match value {
-            'U' => Ok(Self::Up),
-            'D' => Ok(Self::Down),
-            'L' => Ok(Self::Left),
-            'R' => Ok(Self::Right),
-            _ => Err("Invalid direction value!"),
-        }
-    }
-}
+use nom::combinator::map_res;
 
+use crate::error::Result;
+use crate::geometry::{BoundingBox, Direction, Point};
+use crate::parse;
+use crate::solution::{Answer, Solution};
 
-#[derive(PartialEq, Eq, Hash, Default, Copy, Clone)]
-struct Position { x: i32, y: i32 }
+type Position = Point<i32>;
 
 struct RopeBridge<const NODE_COUNT: usize> {
     // head -> node_position[0]
@@ -51,51 +31,25 @@ impl<const NODE_COUNT: usize> RopeBridge<NODE_COUNT> {
         }
     }
 
-    // coordinates:
-    //
-    // y ^
-    //   |
-    //   |
-    //   .------>
-    //          x
-    //
     pub fn move_to(&mut self, direction: Direction, steps: u32) {
         
         for _ in 0..steps {
 
             // Move the head
-            match direction {
-                Direction::Up => { self.node_position[0].y += 1; },
-                Direction::Down => { self.node_position[0].y -= 1; },
-                Direction::Left => { self.node_position[0].x -= 1; },
-                Direction::Right => { self.node_position[0].x += 1; }
-            }
+            self.node_position[0] += direction.vector();
 
-            // move other nodes
+            // move other nodes, one step towards the previous one when they are not touching anymore
             for i in 1..NODE_COUNT {
-                if Self::get_distance(&self.node_position[i], &self.node_position[i-1]) > 1 {
-                    match self.node_position[i-1].x - self.node_position[i].x {
-                        1|2 => self.node_position[i].x += 1,      // moved right
-                        -1|-2 => self.node_position[i].x -= 1,     // moved left
-                        _ => (),
-                    }
-                    match self.node_position[i-1].y - self.node_position[i].y {
-                        1|2 => self.node_position[i].y += 1,      // moved up
-                        -1|-2 => self.node_position[i].y -= 1,     // moved down
-                        _ => (),
-                    }
+                if self.node_position[i].chebyshev(&self.node_position[i-1]) > 1 {
+                    let delta = self.node_position[i-1] - self.node_position[i];
+                    self.node_position[i] += Point::new(delta.x.signum(), delta.y.signum());
                 }
             }
             
-            self.tail_visited_positions.insert(self.node_position[NODE_COUNT-1].clone());
+            self.tail_visited_positions.insert(self.node_position[NODE_COUNT-1]);
         }
     }
 
-
-    fn get_distance(p1: &Position, p2: &Position) -> u32 {
-        max(p1.x.abs_diff(p2.x), p1.y.abs_diff(p2.y))
-    }
-
     pub fn get_tail_number_of_visited_positions(&self) -> u32 {
         self.tail_visited_positions.len() as u32
     }
@@ -104,22 +58,13 @@ impl<const NODE_COUNT: usize> RopeBridge<NODE_COUNT> {
     #[allow(dead_code)]
     fn display_bridge(&self) {
 
-        // find clipping area
-        let mut min_x = 0i32;
-        let mut min_y = 0i32;
-        let mut max_x = 0i32;
-        let mut max_y = 0i32;
-
-        self.node_position.into_iter().for_each(|pos| {
-            min_x = min(min_x, pos.x);
-            min_y = min(min_y, pos.y);
-            max_x = max(max_x, pos.x);
-            max_y = max(max_y, pos.y);
-        });
-
-        for y in (min_y..max_y+1).rev() {
-            for x in min_x..max_x + 1 {
-                match (0..NODE_COUNT).position( |i| self.node_position[i] == Position { x: x, y: y } ) {
+        // clipping area, always showing the start position
+        let mut bbox = BoundingBox::new(Position::default());
+        self.node_position.iter().for_each(|&pos| bbox.include(pos));
+
+        for y in bbox.min.y..=bbox.max.y {
+            for x in bbox.min.x..=bbox.max.x {
+                match (0..NODE_COUNT).position( |i| self.node_position[i] == Point::new(x, y) ) {
                     None => eprint!("."),
                     Some(i) => {
                         if i == 0 {
@@ -137,33 +82,55 @@ impl<const NODE_COUNT: usize> RopeBridge<NODE_COUNT> {
     
 }
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u32 {
-    let file = File::open(filename).unwrap();
-    let mut game = RopeBridge::<2>::new();
-    for line in BufReader::new(file).lines() {
-        let line = line.unwrap();
-        let (_, (dir, steps)) = parse_line(&line).unwrap();
-        game.move_to(dir, steps);
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    type Input = Vec<(Direction, u32)>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_motions(input)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(count_tail_positions::<2>(input).into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(count_tail_positions::<10>(input).into())
     }
-    game.get_tail_number_of_visited_positions()
 }
 
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> u32 {
-    let file = File::open(filename).unwrap();
-    let mut game = RopeBridge::<10>::new();
-    for line in BufReader::new(file).lines() {
-        let line = line.unwrap();
-        let (_, (dir, steps)) = parse_line(&line).unwrap();
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let motions = Day09::parse_file(filename)?;
+    Ok(count_tail_positions::<2>(&motions))
+}
+
+
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let motions = Day09::parse_file(filename)?;
+    Ok(count_tail_positions::<10>(&motions))
+}
+
+
+fn count_tail_positions<const NODE_COUNT: usize>(motions: &[(Direction, u32)]) -> u32 {
+    let mut game = RopeBridge::<NODE_COUNT>::new();
+    for &(dir, steps) in motions {
         game.move_to(dir, steps);
     }
     game.get_tail_number_of_visited_positions()
 }
 
 
+fn parse_motions(input: &str) -> Result<Vec<(Direction, u32)>> {
+    parse::each_line(input, parse_line)
+}
+
+
 fn parse_line(input: &str) -> IResult<&str, (Direction, u32)> {
     separated_pair(
-        map(one_of("UDLR"), |c| Direction::try_from(c).unwrap()),
+        map_res(one_of("UDLR"), Direction::try_from),
         char(' '),
         u32
     )(input)
@@ -175,13 +142,13 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        assert_eq!(super::run_part_1("test_input/day09.txt"), 13);
+        assert_eq!(super::run_part_1("test_input/day09.txt").unwrap(), 13);
     }
 
     #[test]
     fn test_part2() {
-        //assert_eq!(super::run_part_2("test_input/day09.txt"), 1);
-        assert_eq!(super::run_part_2("test_input/day09b.txt"), 36);
+        //assert_eq!(super::run_part_2("test_input/day09.txt").unwrap(), 1);
+        assert_eq!(super::run_part_2("test_input/day09b.txt").unwrap(), 36);
     }
 }
 
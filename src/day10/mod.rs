@@ -1,5 +1,4 @@
-use std::{fs::File, path::Path};
-use std::io::{BufReader, BufRead};
+use std::path::Path;
 use std::fmt;
 
 use nom::IResult;
@@ -9,93 +8,67 @@ use nom::branch::alt;
 use nom::sequence::preceded;
 use nom::combinator::map;
 
-#[derive(Debug, PartialEq)]
-enum Instruction {
+use crate::error::{AocError, Result};
+use crate::grid::Grid;
+use crate::parse;
+use crate::solution::{Answer, Solution};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Instruction {
     Noop,
     Addx(i32),
 }
 
+// Width of the screen, its height depends on the length of the program
+const SCREEN_WIDTH: usize = 40;
+
 #[derive(Debug)]
-struct CRT<const ROWS: usize, const COLUMNS: usize> {
-    pixels: [[bool; COLUMNS];ROWS],
+#[allow(clippy::upper_case_acronyms)]
+struct CRT {
+    screen: Grid<bool>,
     sprite_column: i32,
-    current_pixel_column: usize,
-    current_pixel_row: usize,
-}
-
-impl<const ROWS: usize, const COLUMNS: usize> Default for CRT<ROWS, COLUMNS> {
-    fn default() -> Self {
-        Self {
-            pixels: [[false; COLUMNS]; ROWS],
-            sprite_column: 1,
-            current_pixel_column: 0,
-            current_pixel_row: 0,
-        }
-    }
+    current_pixel: usize,
 }
 
-impl<const ROWS: usize, const COLUMNS: usize> fmt::Display for CRT<ROWS, COLUMNS> {
+impl fmt::Display for CRT {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut s = String::with_capacity(ROWS*(COLUMNS+1));
-        for r in 0..ROWS {
-            for c in 0..COLUMNS {
-                s.push(if self.pixels[r][c] { '#' } else { '.' })
-            }
-            s.push('\n');
-        }
-        f.write_str(&s)
+        f.write_str(&self.screen.render(|&pixel| if pixel { '#' } else { '.' }))
     }
 }
 
-impl<const ROWS: usize, const COLUMNS: usize> From<&str> for CRT<ROWS, COLUMNS> {
-    fn from(s: &str) -> Self {
-        let mut row = 0;
-        let mut col = 0;
-        let mut crt = CRT::default();
-        for c in s.chars() {
-            crt.pixels[row][col] = c == '#';
-            col += 1;
-            if col == COLUMNS {
-                row += 1;
-                col = 0;
-            }
-            if row == ROWS {
-                break;
-            }
-        };
-        crt
+impl TryFrom<&str> for CRT {
+    type Error = AocError;
+
+    fn try_from(s: &str) -> Result<Self> {
+        Ok(Self {
+            screen: Grid::parse(s, |_, c| Ok(c == '#'))?,
+            sprite_column: 1,
+            current_pixel: 0,
+        })
     }
 }
 
 
-impl<const ROWS: usize, const COLUMNS: usize> CRT<ROWS, COLUMNS> {
+impl CRT {
 
-    fn new() -> Self {
-        Self::default()
+    // A screen with enough rows to draw one pixel per cycle
+    fn new(columns: usize, cycles: usize) -> Self {
+        Self {
+            screen: Grid::new(columns, cycles.div_ceil(columns), false),
+            sprite_column: 1,
+            current_pixel: 0,
+        }
     }
 
     fn move_sprite(&mut self, val: i32) {
         self.sprite_column += val;
     }
 
-    // pixel_position starts at 1
     fn set_pixel(&mut self) {
-        println!("{}", self);
-
-        let current_pixel_row = self.current_pixel_row;
-        let current_pixel_column = self.current_pixel_column;
-        self.pixels[current_pixel_row][current_pixel_column] =
-                self.current_pixel_column.abs_diff(self.sprite_column as usize) <= 1;
-
-        self.advance_pixel();
-    }
-
-    fn advance_pixel(&mut self) {
-        self.current_pixel_column += 1;
-        if self.current_pixel_column == COLUMNS {
-            self.current_pixel_row += 1;
-            self.current_pixel_column = 0;
-        }
+        let column = self.current_pixel % self.screen.width();
+        let row = self.current_pixel / self.screen.width();
+        self.screen[(column, row)] = (column as i32 - self.sprite_column).abs() <= 1;
+        self.current_pixel += 1;
     }
 }
 
@@ -108,23 +81,49 @@ fn parse_instruction(input: &str) -> IResult<&str, Instruction> {
                 tag("addx "),
                 i32
             ),
-            |v| Instruction::Addx(v)
+            Instruction::Addx
         )
     ))(input)
 }
 
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> i32 {
-    let file = File::open(filename).unwrap();
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    type Input = Vec<Instruction>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_program(input)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(calculate_part_2(input).into())
+    }
+}
+
+
+fn parse_program(input: &str) -> Result<Vec<Instruction>> {
+    parse::each_line(input, parse_instruction)
+}
+
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<i32> {
+    let program = Day10::parse_file(filename)?;
+    Ok(calculate_part_1(&program))
+}
+
+fn calculate_part_1(program: &[Instruction]) -> i32 {
     //let mut program: Vec<InstructionExecution> = Vec::new();
     let mut current_cycle = 1u32;
     let mut current_register_value = 1i32;
     let mut signal_strength_sum = 0i32;
 
-    for line in BufReader::new(file).lines() {
-        let line = line.unwrap();
-        let (_, instr) = parse_instruction(&line).unwrap();
-
+    for &instr in program {
         let cycle_after: u32;
         let register_value_after: i32;
 
@@ -157,13 +156,20 @@ pub fn run_part_1<P: AsRef<Path>>(filename: P) -> i32 {
     signal_strength_sum
 }
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> String {
-    let file = File::open(filename).unwrap();
-    let mut crt = CRT::<6, 40>::new();
-    for line in BufReader::new(file).lines() {        
-        let line = line.unwrap();
-        let (_, instr) = parse_instruction(&line).unwrap();
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<String> {
+    let program = Day10::parse_file(filename)?;
+    Ok(calculate_part_2(&program))
+}
 
+fn calculate_part_2(program: &[Instruction]) -> String {
+    let cycles = program.iter()
+        .map(|instr| match instr {
+            Instruction::Noop => 1,
+            Instruction::Addx(_) => 2,
+        })
+        .sum();
+    let mut crt = CRT::new(SCREEN_WIDTH, cycles);
+    for &instr in program {
         match instr {
             Instruction::Noop => {
                 crt.set_pixel();
@@ -175,7 +181,6 @@ pub fn run_part_2<P: AsRef<Path>>(filename: P) -> String {
             },
         }
     }
-    println!("{}", crt);
     crt.to_string()
 }
 
@@ -187,13 +192,13 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        assert_eq!(super::run_part_1("test_input/day10.txt"), 13140);
+        assert_eq!(super::run_part_1("test_input/day10.txt").unwrap(), 13140);
     }
 
     #[test]
     fn test_part2() {
         assert_eq!(
-            super::run_part_2("test_input/day10.txt"),
+            super::run_part_2("test_input/day10.txt").unwrap(),
             concat!(
                 "##..##..##..##..##..##..##..##..##..##..\n",
                 "###...###...###...###...###...###...###.\n",
@@ -204,4 +209,14 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn test_screen_height() {
+        // 50 cycles draw one full row and the start of a second one
+        let program = vec![super::Instruction::Noop; 50];
+        let screen = super::calculate_part_2(&program);
+        assert_eq!(screen.lines().count(), 2);
+        assert_eq!(super::CRT::try_from(screen.as_str()).unwrap().to_string(), screen);
+        assert!(super::CRT::try_from("").is_err());
+    }
 }
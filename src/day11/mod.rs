@@ -1,138 +1,448 @@
+use std::collections::HashMap;
+use std::fmt::{self, Write};
 use std::path::Path;
-use std::fs::read_to_string;
-use std::cell::RefCell;
+use std::str::FromStr;
 
+use num_bigint::BigUint;
 
-const TURNS: u32 = 20;
+use crate::error::{AocError, Result};
+use crate::parse;
+use crate::solution::{Answer, Solution};
 
-#[derive(Debug, PartialEq)]
-enum Operator {
-    Plus,
-    Times,
+
+
+/// How the worry level of an item goes down after a monkey has inspected it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relief {
+    /// Divide the worry level by a number, rounding down.
+    DivideBy(u64),
+    /// Keep the remainder modulo the least common multiple of all the tests, which does not
+    /// change where the items are thrown.
+    ModuloLcm,
+    /// No relief at all: the worry levels are big integers, so only a few rounds are practical.
+    None,
 }
 
-impl Operator {
-    fn apply(&self, left: u32, right: u32) -> u32 {
-        match self {
-            Self::Plus => left + right,
-            Self::Times => left * right,
+/// Parameters of the monkey simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Simulation {
+    pub rounds: u64,
+    pub relief: Relief,
+}
+
+impl Simulation {
+    pub const PART_1: Self = Self { rounds: 20, relief: Relief::DivideBy(3) };
+    pub const PART_2: Self = Self { rounds: 10_000, relief: Relief::ModuloLcm };
+}
+
+
+/// One item inspected by a monkey. Worry levels are written in decimal since they can be big
+/// integers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inspection {
+    pub monkey: usize,
+    pub worry_level: String,
+    pub after_operation: String,
+    pub after_relief: String,
+    /// Index of the test of the monkey which passed, None when they all failed
+    pub test: Option<usize>,
+    pub target: usize,
+}
+
+/// The inspections of a round, and the items held and counts of inspected items at its end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round {
+    pub number: u64,
+    pub inspections: Vec<Inspection>,
+    pub holding: Vec<Vec<String>>,
+    pub items_inspected: Vec<u64>,
+}
+
+/// Record of a simulation, see `trace`.
+#[derive(Debug, Clone)]
+pub struct Trace {
+    monkeys: Vec<Monkey>,
+    reduction: Reduction,
+    pub rounds: Vec<Round>,
+}
+
+/// Output format of a trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TraceFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for TraceFormat {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(AocError::invalid(format!("unknown format '{s}', expected text or json"))),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum Operand {
+impl Trace {
+
+    pub fn render(&self, format: TraceFormat) -> String {
+        match format {
+            TraceFormat::Text => self.to_text(),
+            TraceFormat::Json => self.to_json(),
+        }
+    }
+
+    // Worded like the examples of the puzzle
+    fn to_text(&self) -> String {
+        let mut s = String::new();
+        for round in &self.rounds {
+            writeln!(s, "== Round {} ==", round.number).unwrap();
+            for (i, monkey) in self.monkeys.iter().enumerate() {
+                writeln!(s, "Monkey {i}:").unwrap();
+                for inspection in round.inspections.iter().filter(|inspection| inspection.monkey == i) {
+                    writeln!(s, "  Monkey inspects an item with a worry level of {}.", inspection.worry_level).unwrap();
+                    writeln!(s, "    {}", monkey.operation.describe(&inspection.after_operation)).unwrap();
+                    match self.reduction {
+                        Reduction::DivideBy(n) => writeln!(
+                            s, "    Monkey gets bored with item. Worry level is divided by {n} to {}.",
+                            inspection.after_relief
+                        ).unwrap(),
+                        Reduction::Modulo(m) => writeln!(
+                            s, "    Worry level is reduced modulo {m} to {}.", inspection.after_relief
+                        ).unwrap(),
+                        Reduction::None => (),
+                    }
+                    // The tests are checked until one passes
+                    let checked = inspection.test.map_or(monkey.tests.len(), |test| test + 1);
+                    for (k, test) in monkey.tests[..checked].iter().enumerate() {
+                        writeln!(
+                            s, "    Current worry level is {}divisible by {}.",
+                            if inspection.test == Some(k) { "" } else { "not " }, test.divisor
+                        ).unwrap();
+                    }
+                    writeln!(
+                        s, "    Item with worry level {} is thrown to monkey {}.",
+                        inspection.after_relief, inspection.target
+                    ).unwrap();
+                }
+            }
+            writeln!(s).unwrap();
+            writeln!(s, "After round {}, the monkeys are holding items with these worry levels:", round.number).unwrap();
+            for (i, held) in round.holding.iter().enumerate() {
+                writeln!(s, "Monkey {i}: {}", held.join(", ")).unwrap();
+            }
+            writeln!(s).unwrap();
+            writeln!(s, "== After round {} ==", round.number).unwrap();
+            for (i, count) in round.items_inspected.iter().enumerate() {
+                writeln!(s, "Monkey {i} inspected items {count} times.").unwrap();
+            }
+            writeln!(s).unwrap();
+        }
+        s
+    }
+
+    // One round per line, worry levels are JSON numbers of any size
+    fn to_json(&self) -> String {
+        let rounds: Vec<String> = self.rounds.iter()
+            .map(|round| {
+                let inspections: Vec<String> = round.inspections.iter()
+                    .map(|inspection| format!(
+                        r#"{{"monkey": {}, "worry_level": {}, "after_operation": {}, "after_relief": {}, "divisible": {}, "test": {}, "target": {}}}"#,
+                        inspection.monkey, inspection.worry_level, inspection.after_operation,
+                        inspection.after_relief, inspection.test.is_some(),
+                        inspection.test.map_or("null".to_string(), |test| test.to_string()), inspection.target
+                    ))
+                    .collect();
+                let holding: Vec<String> = round.holding.iter()
+                    .map(|held| format!("[{}]", held.join(", ")))
+                    .collect();
+                let counts: Vec<String> = round.items_inspected.iter().map(u64::to_string).collect();
+                format!(
+                    r#"  {{"round": {}, "inspections": [{}], "holding": [{}], "items_inspected": [{}]}}"#,
+                    round.number, inspections.join(", "), holding.join(", "), counts.join(", ")
+                )
+            })
+            .collect();
+        if rounds.is_empty() {
+            "[]\n".to_string()
+        } else {
+            format!("[\n{}\n]\n", rounds.join(",\n"))
+        }
+    }
+}
+
+
+// The numbers a worry level can be made of
+trait WorryLevel: Clone + From<u64> + fmt::Display {
+    // None when the result does not fit
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+    fn div(&self, divisor: u64) -> Self;
+    fn rem(&self, divisor: u64) -> u64;
+}
+
+impl WorryLevel for u64 {
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        u64::checked_add(*self, *other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        u64::checked_sub(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        u64::checked_mul(*self, *other)
+    }
+
+    fn div(&self, divisor: u64) -> Self {
+        self / divisor
+    }
+
+    fn rem(&self, divisor: u64) -> u64 {
+        self % divisor
+    }
+}
+
+impl WorryLevel for BigUint {
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        (self >= other).then(|| self - other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+
+    fn div(&self, divisor: u64) -> Self {
+        self / divisor
+    }
+
+    fn rem(&self, divisor: u64) -> u64 {
+        // The remainder is below the divisor
+        u64::try_from(self % divisor).unwrap()
+    }
+}
+
+
+/// Expression giving the new worry level of an item from the old one.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Operation {
     Old,
-    Value(u32),
+    Value(u64),
+    Add(Box<Operation>, Box<Operation>),
+    Sub(Box<Operation>, Box<Operation>),
+    Mul(Box<Operation>, Box<Operation>),
 }
 
-impl Operand {
-    fn get_value(&self, old: u32) -> u32 {
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Old => old,
-            Self::Value(v) => *v,
+            Self::Old => write!(f, "old"),
+            Self::Value(v) => write!(f, "{v}"),
+            Self::Add(a, b) => Self::fmt_binary(f, a, '+', b, self.precedence()),
+            Self::Sub(a, b) => Self::fmt_binary(f, a, '-', b, self.precedence()),
+            Self::Mul(a, b) => Self::fmt_binary(f, a, '*', b, self.precedence()),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
-struct Operation {
-    op: Operator,
-    left: Operand,
-    right: Operand,
-}
+impl Operation {
 
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Add(..) | Self::Sub(..) => 1,
+            Self::Mul(..) => 2,
+            Self::Old | Self::Value(_) => 3,
+        }
+    }
 
-impl Operation {
-    fn execute(&self, old: u32) -> u32 {
-        self.op.apply(
-            self.left.get_value(old),
-            self.right.get_value(old)
-        )
+    // Parentheses are only written when needed: a - (b + c), (a + b) * c
+    fn fmt_binary(f: &mut fmt::Formatter, a: &Self, op: char, b: &Self, precedence: u8) -> fmt::Result {
+        if a.precedence() < precedence {
+            write!(f, "({a})")?;
+        } else {
+            write!(f, "{a}")?;
+        }
+        write!(f, " {op} ")?;
+        if b.precedence() < precedence || (b.precedence() == precedence && op == '-') {
+            write!(f, "({b})")
+        } else {
+            write!(f, "{b}")
+        }
+    }
+
+    // As in the puzzle text ("Worry level is multiplied by 19 to 1501.") for the usual operations
+    fn describe(&self, result: &str) -> String {
+        let simple = match self {
+            Self::Add(a, b) => match (a.as_ref(), b.as_ref()) {
+                (Self::Old, Self::Value(v)) | (Self::Value(v), Self::Old) => Some(format!("increases by {v}")),
+                _ => None,
+            },
+            Self::Sub(a, b) => match (a.as_ref(), b.as_ref()) {
+                (Self::Old, Self::Value(v)) => Some(format!("decreases by {v}")),
+                _ => None,
+            },
+            Self::Mul(a, b) => match (a.as_ref(), b.as_ref()) {
+                (Self::Old, Self::Old) => Some("is multiplied by itself".to_string()),
+                (Self::Old, Self::Value(v)) | (Self::Value(v), Self::Old) => Some(format!("is multiplied by {v}")),
+                _ => None,
+            },
+            _ => None,
+        };
+        match simple {
+            Some(change) => format!("Worry level {change} to {result}."),
+            None => format!("Worry level is set to {self} = {result}."),
+        }
+    }
+
+    // When a modulus is given only the remainder matters, so the results of sub-expressions are
+    // reduced and a subtraction is done as an addition of the opposite modulo `modulus`.
+    // None when the result does not fit, or is negative
+    fn execute<W: WorryLevel>(&self, old: &W, modulus: Option<u64>) -> Option<W> {
+        let operand = |e: &Self| -> Option<W> {
+            match (e, modulus) {
+                (Self::Old, _) => Some(old.clone()),
+                (Self::Value(v), _) => Some(W::from(*v)),
+                (_, Some(m)) => e.execute(old, modulus).map(|value| W::from(value.rem(m))),
+                (_, None) => e.execute(old, modulus),
+            }
+        };
+        match self {
+            Self::Old => Some(old.clone()),
+            Self::Value(v) => Some(W::from(*v)),
+            Self::Add(a, b) => operand(a)?.checked_add(&operand(b)?),
+            Self::Mul(a, b) => operand(a)?.checked_mul(&operand(b)?),
+            Self::Sub(a, b) => match modulus {
+                Some(m) => operand(a)?.checked_add(&W::from(m - operand(b)?.rem(m))),
+                None => operand(a)?.checked_sub(&operand(b)?),
+            },
+        }
+    }
+
+    fn new_add(a: Self, b: Self) -> Self {
+        Self::Add(Box::new(a), Box::new(b))
+    }
+
+    fn new_sub(a: Self, b: Self) -> Self {
+        Self::Sub(Box::new(a), Box::new(b))
+    }
+
+    fn new_mul(a: Self, b: Self) -> Self {
+        Self::Mul(Box::new(a), Box::new(b))
     }
 }
 
 
-#[derive(Debug, PartialEq)]
-struct Monkey {
-    items: Vec<u32>,
+/// Throw the item to `target` when its worry level is divisible by `divisor`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Test {
+    divisor: u64,
+    target: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Monkey {
+    items: Vec<u64>,
     operation: Operation,
-    test_divisible: u32,
-    monkey_if_true: u32,
-    monkey_if_false: u32,
-    items_inspected: u32,
+    // Checked in order, the item goes to `monkey_if_false` when none of them passes
+    tests: Vec<Test>,
+    monkey_if_false: usize,
+}
+
+// Relief with its modulus resolved
+#[derive(Debug, Clone, Copy)]
+enum Reduction {
+    DivideBy(u64),
+    Modulo(u64),
+    None,
+}
+
+impl Reduction {
+    fn apply<W: WorryLevel>(self, worry_level: W) -> W {
+        match self {
+            Self::DivideBy(n) => worry_level.div(n),
+            Self::Modulo(m) => W::from(worry_level.rem(m)),
+            Self::None => worry_level,
+        }
+    }
+
+    fn modulus(self) -> Option<u64> {
+        match self {
+            Self::Modulo(m) => Some(m),
+            _ => None,
+        }
+    }
 }
 
 impl Monkey {
-    fn do_turn(&mut self, monkeys: &MonkeyGroup) {
-        for item in &self.items {
+    // Worry level of an item while this monkey inspects it, before relief
+    fn inspect<W: WorryLevel>(&self, item: &W, reduction: Reduction) -> Result<W> {
+        self.operation.execute(item, reduction.modulus())
+            .ok_or_else(|| AocError::invalid(format!(
+                "the worry level of new = {} does not fit or is negative for old = {item}",
+                self.operation
+            )))
+    }
 
-            // inspect item
-            self.items_inspected += 1;
+    // Index of the test which passed, if any, and the monkey to which the item is thrown
+    fn target<W: WorryLevel>(&self, worry_level: &W) -> (Option<usize>, usize) {
+        match self.tests.iter().position(|test| worry_level.rem(test.divisor) == 0) {
+            Some(i) => (Some(i), self.tests[i].target),
+            None => (None, self.monkey_if_false),
+        }
+    }
 
-            // update worry level of the item
-            let mut new_worry_level: u32 = self.operation.execute(*item);
+    fn divisors(&self) -> impl Iterator<Item = u64> + '_ {
+        self.tests.iter().map(|test| test.divisor)
+    }
+}
 
-            // update worry level after inspection (divide by 3)
-            new_worry_level = (new_worry_level as f32 / 3.0).floor() as u32;
+pub type MonkeyGroup = Vec<Monkey>;
 
-            // throw item to other monkey
-            if new_worry_level % self.test_divisible == 0 {
-                monkeys[self.monkey_if_true as usize].borrow_mut().items.push(new_worry_level);
-            } else {
-                monkeys[self.monkey_if_false as usize].borrow_mut().items.push(new_worry_level);
+// Every item must be thrown to another monkey of the group
+fn validate(monkeys: &[Monkey]) -> Result<()> {
+    for (i, monkey) in monkeys.iter().enumerate() {
+        if monkey.divisors().any(|divisor| divisor == 0) {
+            return Err(AocError::invalid(format!("monkey {i} tests divisibility by 0")));
+        }
+        let targets = monkey.tests.iter().map(|test| test.target).chain([monkey.monkey_if_false]);
+        for target in targets {
+            if target == i {
+                return Err(AocError::invalid(format!("monkey {i} throws items to itself")));
+            }
+            if target >= monkeys.len() {
+                return Err(AocError::invalid(format!("monkey {i} throws items to monkey {target}, which does not exist")));
             }
         }
-        self.items.clear();
     }
+    Ok(())
 }
 
-type MonkeyGroup = Vec<RefCell<Monkey>>;
-
 
 mod parser{
     
-    use std::cell::RefCell;
+    use crate::parse::{ws, cr};
 
     use nom::IResult;
-    use nom::error::ParseError;
-    use nom::character::complete::{u32, char, multispace0, line_ending};
+    use nom::character::complete::{u32, u64, char, one_of, line_ending};
     use nom::bytes::complete::tag;
-    use nom::sequence::{preceded, delimited, tuple};
-    use nom::multi::{separated_list1, many1};
+    use nom::sequence::{preceded, delimited, terminated, pair, tuple};
+    use nom::multi::{separated_list1, many1, fold_many0};
     use nom::branch::alt;
-    use nom::combinator::map;
+    use nom::combinator::{map, opt};
 
-    type Operator = super::Operator;
-    type Operand = super::Operand;
     type Operation = super::Operation;
+    type Test = super::Test;
     type Monkey = super::Monkey;
     type MonkeyGroup = super::MonkeyGroup;
 
-    /// A combinator that takes a parser `inner` and produces a parser that also consumes leading
-    /// whitespace, returning the output of `inner`.
-    fn ws<'a, F, O, E: ParseError<&'a str>>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
-        where
-        F: FnMut(&'a str) -> IResult<&'a str, O, E>,
-    {
-        preceded(
-            multispace0,
-            inner
-        )
-    }
-
-    /// A combinator that takes a parser `inner` and produces a parser that also consumes one leading
-    /// carriage return, returning the output of `inner`.
-    fn cr<'a, F, O, E: ParseError<&'a str>>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
-        where
-        F: FnMut(&'a str) -> IResult<&'a str, O, E>,
-    {
-        preceded(
-            line_ending,
-            inner
-        )
-    }
-
     fn parse_monkey_id(input: &str) -> IResult<&str, u32> {
         delimited(
             tag("Monkey "),
@@ -141,46 +451,59 @@ mod parser{
         )(input)
     }
 
-    fn parse_starting_items(input: &str) -> IResult<&str, Vec<u32>> {
+    fn parse_starting_items(input: &str) -> IResult<&str, Vec<u64>> {
         preceded(
             tag("  Starting items: "),
             separated_list1(
                 tag(", "),
-                u32
+                u64
             )
         )(input)
     }
 
-    fn parse_operand(input: &str) -> IResult<&str, Operand> {
-        alt((
-            map(tag("old"), |_| Operand::Old),
-            map(u32, |v| Operand::Value(v)),
-        ))(input)
+    // old, a number or an expression between parentheses
+    fn parse_factor(input: &str) -> IResult<&str, Operation> {
+        ws(alt((
+            map(tag("old"), |_| Operation::Old),
+            map(u64, Operation::Value),
+            delimited(char('('), parse_expression, ws(char(')'))),
+        )))(input)
     }
 
-    fn parse_operator(input: &str) -> IResult<&str, Operator> {
-        alt((
-            map(char('+'), |_| Operator::Plus),
-            map(char('*'), |_| Operator::Times),
-        ))(input)
+    // Products, which take precedence over sums and differences
+    fn parse_term(input: &str) -> IResult<&str, Operation> {
+        let (input, first) = parse_factor(input)?;
+        fold_many0(
+            preceded(ws(char('*')), parse_factor),
+            move || first.clone(),
+            Operation::new_mul
+        )(input)
+    }
+
+    // Terms are combined from left to right: a - b + c is (a - b) + c
+    fn parse_expression(input: &str) -> IResult<&str, Operation> {
+        let (input, first) = parse_term(input)?;
+        fold_many0(
+            pair(ws(one_of("+-")), parse_term),
+            move || first.clone(),
+            |acc, (op, term)| match op {
+                '+' => Operation::new_add(acc, term),
+                _ => Operation::new_sub(acc, term),
+            }
+        )(input)
     }
 
     fn parse_operation(input: &str) -> IResult<&str, Operation> {
-        map(
-            tuple((
-                tag("  Operation: new = "),
-                ws(parse_operand),
-                ws(parse_operator),
-                ws(parse_operand),
-            )),
-            |(_, left, op, right)| Operation {op: op, left: left, right: right}
+        preceded(
+            tag("  Operation: new ="),
+            parse_expression
         )(input)
     }
 
-    fn parse_test(input: &str) -> IResult<&str, u32> {
+    fn parse_test(input: &str) -> IResult<&str, u64> {
         preceded(
             tag("  Test: divisible by "),
-            u32
+            u64
         )(input)
     }
 
@@ -198,37 +521,47 @@ mod parser{
         )(input)
     }
 
+    // A test and the monkey receiving the item when it passes
+    fn parse_condition(input: &str) -> IResult<&str, Test> {
+        map(
+            pair(parse_test, cr(parse_if_true)),
+            |(divisor, target)| Test{ divisor, target: target as usize }
+        )(input)
+    }
+
     fn parse_monkey(input: &str) -> IResult<&str, Monkey> {
         map(
             tuple((
                 parse_monkey_id,
                 cr(parse_starting_items),
                 cr(parse_operation),
-                cr(parse_test),
-                cr(parse_if_true),
+                many1(cr(parse_condition)),
                 cr(parse_if_false),
             )),
-            |(_, items, operation, test, if_true, if_false)| Monkey{
-                items: items,
-                operation: operation,
-                test_divisible: test,
-                monkey_if_true: if_true,
-                monkey_if_false: if_false,
-                items_inspected: 0,
+            |(_, items, operation, tests, if_false)| Monkey{
+                items,
+                operation,
+                tests,
+                monkey_if_false: if_false as usize,
             }
         )(input)
     }
 
     pub(super) fn parse_monkeys(input: &str) -> IResult<&str, MonkeyGroup> {
-        separated_list1(
-            many1(line_ending),
-            map(parse_monkey, |monkey| RefCell::new(monkey))
+        terminated(
+            separated_list1(
+                many1(line_ending),
+                parse_monkey
+            ),
+            opt(line_ending)
         )(input)
     }
 
     #[cfg(test)]
     mod test {
 
+        use super::{Operation, Test};
+
         #[test]
         fn test_parse_monkey_id() {
             assert_eq!(
@@ -241,7 +574,7 @@ mod parser{
         fn test_parse_starting_item() {
             assert_eq!(
                 super::parse_starting_items("  Starting items: 2, 4, 10"),
-                Ok(("", vec![2u32, 4, 10]))
+                Ok(("", vec![2u64, 4, 10]))
             );
         }
 
@@ -249,12 +582,34 @@ mod parser{
         fn test_parse_operation() {
             assert_eq!(
                 super::parse_operation("  Operation: new = old * 4"),
-                Ok(("", super::Operation{
-                    op: super::Operator::Times,
-                    left: super::Operand::Old,
-                    right: super::Operand::Value(4)
-                }))
+                Ok(("", Operation::new_mul(Operation::Old, Operation::Value(4))))
+            );
+        }
+
+        #[test]
+        fn test_parse_expression() {
+            let (rest, e) = super::parse_operation("  Operation: new = 2 * (old - 3) * old + 7 - old\n").unwrap();
+            assert_eq!(rest, "\n");
+            assert_eq!(e.to_string(), "2 * (old - 3) * old + 7 - old");
+            assert_eq!(
+                e,
+                Operation::new_sub(
+                    Operation::new_add(
+                        Operation::new_mul(
+                            Operation::new_mul(
+                                Operation::Value(2),
+                                Operation::new_sub(Operation::Old, Operation::Value(3))
+                            ),
+                            Operation::Old
+                        ),
+                        Operation::Value(7)
+                    ),
+                    Operation::Old
+                )
             );
+            let (_, e) = super::parse_operation("  Operation: new = old - (old - 1)").unwrap();
+            assert_eq!(e.to_string(), "old - (old - 1)");
+            assert!(crate::parse::finish("  Operation: new = old * (old + 1", super::parse_operation).is_err());
         }
 
         #[test]
@@ -268,28 +623,36 @@ mod parser{
     If false: throw to monkey 3"#),
                 Ok(("", super::Monkey {
                     items: vec![79, 60, 97],
-                    operation: super::Operation {
-                        op: super::Operator::Times,
-                        left: super::Operand::Old,
-                        right: super::Operand::Old,
-                    },
-                    test_divisible: 13,
-                    monkey_if_true: 1,
+                    operation: Operation::new_mul(Operation::Old, Operation::Old),
+                    tests: vec![Test{ divisor: 13, target: 1 }],
                     monkey_if_false: 3,
-                    items_inspected: 0,
                 }))
             )
         }
+
+        #[test]
+        fn test_parse_multiple_tests() {
+            let (_, monkey) = super::parse_monkey(concat!(
+                "Monkey 0:\n",
+                "  Starting items: 1\n",
+                "  Operation: new = old + 1\n",
+                "  Test: divisible by 2\n",
+                "    If true: throw to monkey 1\n",
+                "  Test: divisible by 3\n",
+                "    If true: throw to monkey 2\n",
+                "    If false: throw to monkey 3\n",
+            )).unwrap();
+            assert_eq!(monkey.tests, vec![Test{ divisor: 2, target: 1 }, Test{ divisor: 3, target: 2 }]);
+            assert_eq!(monkey.monkey_if_false, 3);
+        }
     }
 
 }
 
-
-fn find_most_active_monkeys(monkeys: &MonkeyGroup) -> (u32, u32) {
-    let mut first: u32 = 0;
-    let mut second: u32 = 0;
-    for monkey in monkeys {
-        let items_inspected = monkey.borrow().items_inspected;
+fn find_most_active_monkeys(items_inspected: &[u64]) -> (u64, u64) {
+    let mut first: u64 = 0;
+    let mut second: u64 = 0;
+    for &items_inspected in items_inspected {
         if items_inspected >= first {
             second = first;
             first = items_inspected;
@@ -301,21 +664,197 @@ fn find_most_active_monkeys(monkeys: &MonkeyGroup) -> (u32, u32) {
 }
 
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u32 {
-    let input = read_to_string(filename).unwrap();
-    let (_, monkeys) = parser::parse_monkeys(&input).unwrap();
-    for _ in 0..TURNS {
-        for monkey in &monkeys {
-            monkey.borrow_mut().do_turn(&monkeys);
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    type Input = MonkeyGroup;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let monkeys = parse::finish(input, parser::parse_monkeys)?;
+        validate(&monkeys)?;
+        Ok(monkeys)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(simulate(input, &Simulation::PART_1)?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(simulate(input, &Simulation::PART_2)?.into())
+    }
+}
+
+
+/// Play the rounds of the simulation and return the level of monkey business: the product of
+/// the numbers of items inspected by the two most active monkeys.
+pub fn simulate(monkeys: &MonkeyGroup, simulation: &Simulation) -> Result<u128> {
+    run_simulation(monkeys, simulation, None)
+}
+
+/// Play the simulation and record every inspection and the state of the monkeys after each round.
+pub fn trace(monkeys: &MonkeyGroup, simulation: &Simulation) -> Result<Trace> {
+    let mut rounds = Vec::new();
+    run_simulation(monkeys, simulation, Some(&mut rounds))?;
+    Ok(Trace {
+        monkeys: monkeys.clone(),
+        reduction: reduction(monkeys, simulation.relief)?,
+        rounds,
+    })
+}
+
+fn reduction(monkeys: &[Monkey], relief: Relief) -> Result<Reduction> {
+    match relief {
+        Relief::DivideBy(0) => Err(AocError::invalid("the worry levels cannot be divided by 0")),
+        Relief::DivideBy(n) => Ok(Reduction::DivideBy(n)),
+        Relief::ModuloLcm => {
+            let modulus = monkeys.iter().flat_map(Monkey::divisors).try_fold(1, lcm)
+                .ok_or_else(|| AocError::invalid("divisors' lcm overflows u64"))?;
+            Ok(Reduction::Modulo(modulus))
         }
+        Relief::None => Ok(Reduction::None),
     }
-    let (first, second) = find_most_active_monkeys(&monkeys);
-    first * second
 }
 
+fn run_simulation(monkeys: &[Monkey], simulation: &Simulation, trace: Option<&mut Vec<Round>>) -> Result<u128> {
+    let reduction = reduction(monkeys, simulation.relief)?;
+    let items_inspected = match (reduction, trace) {
+        (Reduction::None, trace) => play_rounds::<BigUint>(monkeys, simulation.rounds, reduction, trace)?,
+        (Reduction::Modulo(modulus), None) => count_inspections(monkeys, simulation.rounds, modulus)?,
+        (_, trace) => play_rounds::<u64>(monkeys, simulation.rounds, reduction, trace)?,
+    };
+    // The product of two counts always fits in 128 bits
+    let (first, second) = find_most_active_monkeys(&items_inspected);
+    Ok(first as u128 * second as u128)
+}
+
+// Returns the number of items inspected by each monkey.
+// The rounds are recorded in `trace` when it is given
+fn play_rounds<W: WorryLevel>(
+    monkeys: &[Monkey],
+    rounds: u64,
+    reduction: Reduction,
+    mut trace: Option<&mut Vec<Round>>,
+) -> Result<Vec<u64>> {
+    // Items held by each monkey, thrown from one buffer to another
+    let mut items: Vec<Vec<W>> = monkeys.iter()
+        .map(|monkey| monkey.items.iter().map(|&item| W::from(item)).collect())
+        .collect();
+    let mut items_inspected = vec![0u64; monkeys.len()];
 
-pub fn run_part_2<P: AsRef<Path>>(_filename: P) -> i32 {
-    0
+    for round in 1..=rounds {
+        let mut inspections = Vec::new();
+        for (i, monkey) in monkeys.iter().enumerate() {
+            // No monkey throws to itself (see validate), so its buffer stays empty during its turn
+            let mut held = std::mem::take(&mut items[i]);
+            items_inspected[i] += held.len() as u64;
+            for item in held.drain(..) {
+                let inspected = monkey.inspect(&item, reduction)?;
+                let after_operation = trace.is_some().then(|| inspected.to_string());
+                let worry_level = reduction.apply(inspected);
+                let (test, target) = monkey.target(&worry_level);
+                if let Some(after_operation) = after_operation {
+                    inspections.push(Inspection {
+                        monkey: i,
+                        worry_level: item.to_string(),
+                        after_operation,
+                        after_relief: worry_level.to_string(),
+                        test,
+                        target,
+                    });
+                }
+                items[target].push(worry_level);
+            }
+            // Give the buffer back to keep its allocation
+            items[i] = held;
+        }
+        if let Some(trace) = trace.as_mut() {
+            trace.push(Round {
+                number: round,
+                inspections,
+                holding: items.iter().map(|held| held.iter().map(W::to_string).collect()).collect(),
+                items_inspected: items_inspected.clone(),
+            });
+        }
+    }
+    Ok(items_inspected)
+}
+
+// Same as play_rounds when the worry levels are kept modulo `modulus`, but in a time which does
+// not depend on the number of rounds once the items cycle.
+//
+// The items never interact, so each one is followed alone. Its state at the start of a round
+// (the monkey holding it and its worry level) has a finite number of values: when a state
+// comes back, the rounds in between repeat until the end and their inspections are multiplied.
+fn count_inspections(monkeys: &[Monkey], rounds: u64, modulus: u64) -> Result<Vec<u64>> {
+    let reduction = Reduction::Modulo(modulus);
+    let mut items_inspected = vec![0u64; monkeys.len()];
+
+    for (start, monkey) in monkeys.iter().enumerate() {
+        for &item in &monkey.items {
+            let mut state = (start, item);
+            // Round in which each state was seen, and the monkeys which inspected the item in
+            // each round played so far
+            let mut seen: HashMap<(usize, u64), usize> = HashMap::new();
+            let mut history: Vec<Vec<usize>> = Vec::new();
+
+            while (history.len() as u64) < rounds {
+                if let Some(&first) = seen.get(&state) {
+                    let cycle = &history[first..];
+                    let remaining = rounds - history.len() as u64;
+                    let (full, partial) = (remaining / cycle.len() as u64, remaining % cycle.len() as u64);
+                    for (k, inspected_by) in cycle.iter().enumerate() {
+                        let times = full + u64::from((k as u64) < partial);
+                        for &i in inspected_by {
+                            items_inspected[i] = items_inspected[i].checked_add(times)
+                                .ok_or_else(|| AocError::invalid(format!("monkey {i} inspects more than {} items", u64::MAX)))?;
+                        }
+                    }
+                    break;
+                }
+                seen.insert(state, history.len());
+
+                // The item goes from monkey to monkey during the round, until it is thrown to a
+                // monkey which has already played
+                let (mut holder, mut worry_level) = state;
+                let mut inspected_by = Vec::new();
+                loop {
+                    inspected_by.push(holder);
+                    items_inspected[holder] += 1;
+                    worry_level = reduction.apply(monkeys[holder].inspect(&worry_level, reduction)?);
+                    let (_, target) = monkeys[holder].target(&worry_level);
+                    let played = target < holder;
+                    holder = target;
+                    if played {
+                        break;
+                    }
+                }
+                state = (holder, worry_level);
+                history.push(inspected_by);
+            }
+        }
+    }
+    Ok(items_inspected)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+// None when the result does not fit in 64 bits
+fn lcm(a: u64, b: u64) -> Option<u64> {
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u128> {
+    let monkeys = Day11::parse_file(filename)?;
+    simulate(&monkeys, &Simulation::PART_1)
+}
+
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u128> {
+    let monkeys = Day11::parse_file(filename)?;
+    simulate(&monkeys, &Simulation::PART_2)
 }
 
 
@@ -325,15 +864,191 @@ pub fn run_part_2<P: AsRef<Path>>(_filename: P) -> i32 {
 #[cfg(test)]
 mod test {
 
+    use super::{Day11, Relief, Simulation, TraceFormat};
+    use crate::solution::Solution;
+
     #[test]
     fn test_part_1() {
-        assert_eq!(super::run_part_1("test_input/day11.txt"), 10605);
+        assert_eq!(super::run_part_1("test_input/day11.txt").unwrap(), 10605);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(super::run_part_2("test_input/day11.txt"), 0);
+        assert_eq!(super::run_part_2("test_input/day11.txt").unwrap(), 2713310158);
     }
 
-}
+    #[test]
+    fn test_lcm() {
+        assert_eq!(super::lcm(4, 6), Some(12));
+        assert_eq!([23, 19, 13, 17].into_iter().try_fold(1, super::lcm), Some(96577));
+        assert_eq!(super::lcm(u64::MAX, u64::MAX), Some(u64::MAX));
+        assert_eq!(super::lcm(u64::MAX, 2), None);
+    }
+
+    #[test]
+    fn test_lcm_overflow() {
+        // 2^32 - 5 and 2^32 + 15 are coprime and their product is above u64::MAX
+        let monkey = |id, divisor, target| format!(concat!(
+            "Monkey {}:\n",
+            "  Starting items: 79\n",
+            "  Operation: new = old + 1\n",
+            "  Test: divisible by {}\n",
+            "    If true: throw to monkey {}\n",
+            "    If false: throw to monkey {}\n",
+        ), id, divisor, target, target);
+        let monkeys = Day11::parse(&(monkey(0, 4294967291u64, 1) + "\n" + &monkey(1, 4294967311u64, 0))).unwrap();
+        assert_eq!(
+            super::simulate(&monkeys, &Simulation::PART_2).unwrap_err().to_string(),
+            "invalid input: divisors' lcm overflows u64"
+        );
+    }
+
+    #[test]
+    fn test_relief() {
+        let monkeys = Day11::parse_file("test_input/day11.txt").unwrap();
+        let simulate = |rounds, relief| super::simulate(&monkeys, &Simulation { rounds, relief });
 
+        // Without relief, the most active monkeys inspect 103 and 99 items in 20 rounds
+        assert_eq!(simulate(20, Relief::None).unwrap(), 103 * 99);
+        assert_eq!(simulate(20, Relief::ModuloLcm).unwrap(), 103 * 99);
+        assert_eq!(simulate(20, Relief::DivideBy(3)).unwrap(), 10605);
+
+        // The worry levels do not fit in 64 bits without the big integers
+        assert!(simulate(20, Relief::DivideBy(1)).is_err());
+        assert!(simulate(20, Relief::DivideBy(0)).is_err());
+    }
+
+    #[test]
+    fn test_cycles() {
+        let monkeys = Day11::parse_file("test_input/day11.txt").unwrap();
+        let modulus = 23 * 19 * 13 * 17;
+        for rounds in [0, 1, 20, 1000, 4321, 10_000] {
+            assert_eq!(
+                super::count_inspections(&monkeys, rounds, modulus).unwrap(),
+                super::play_rounds::<u64>(&monkeys, rounds, super::Reduction::Modulo(modulus), None).unwrap()
+            );
+        }
+
+        // The number of rounds only matters once the items cycle
+        let inspected = super::count_inspections(&monkeys, 1_000_000_000_000, modulus).unwrap();
+        assert!(inspected.iter().sum::<u64>() > 1_000_000_000_000);
+        let (first, second) = super::find_most_active_monkeys(&inspected);
+        let simulation = Simulation { rounds: 1_000_000_000_000, ..Simulation::PART_2 };
+        assert_eq!(super::simulate(&monkeys, &simulation).unwrap(), first as u128 * second as u128);
+        assert!(first as u128 * second as u128 > u64::MAX as u128);
+
+        // Only the inspection counts have to fit in 64 bits
+        let simulation = Simulation { rounds: u64::MAX, ..Simulation::PART_2 };
+        assert!(super::simulate(&monkeys, &simulation).is_err());
+    }
+
+    #[test]
+    fn test_trace() {
+        let monkeys = Day11::parse_file("test_input/day11.txt").unwrap();
+        let trace = super::trace(&monkeys, &Simulation { rounds: 1, ..Simulation::PART_1 }).unwrap();
+        let text = trace.render(TraceFormat::Text);
+        assert!(text.starts_with(concat!(
+            "== Round 1 ==\n",
+            "Monkey 0:\n",
+            "  Monkey inspects an item with a worry level of 79.\n",
+            "    Worry level is multiplied by 19 to 1501.\n",
+            "    Monkey gets bored with item. Worry level is divided by 3 to 500.\n",
+            "    Current worry level is not divisible by 23.\n",
+            "    Item with worry level 500 is thrown to monkey 3.\n",
+        )));
+        assert!(text.contains(concat!(
+            "After round 1, the monkeys are holding items with these worry levels:\n",
+            "Monkey 0: 20, 23, 27, 26\n",
+            "Monkey 1: 2080, 25, 167, 207, 401, 1046\n",
+            "Monkey 2: \n",
+            "Monkey 3: \n",
+            "\n",
+            "== After round 1 ==\n",
+            "Monkey 0 inspected items 2 times.\n",
+        )));
+
+        let trace = super::trace(&monkeys, &Simulation { rounds: 20, relief: Relief::None }).unwrap();
+        assert_eq!(trace.rounds.len(), 20);
+        assert_eq!(trace.rounds[19].items_inspected, vec![99, 97, 8, 103]);
+        let json = trace.render(TraceFormat::Json);
+        assert!(json.starts_with(concat!(
+            "[\n",
+            r#"  {"round": 1, "inspections": [{"monkey": 0, "worry_level": 79, "after_operation": 1501, "#,
+            r#""after_relief": 1501, "divisible": false, "test": null, "target": 3}, "#,
+        )));
+        assert_eq!(json.lines().count(), 22);
+    }
+
+    #[test]
+    fn test_custom_monkeys() {
+        let monkeys = Day11::parse(concat!(
+            "Monkey 0:\n",
+            "  Starting items: 10, 20\n",
+            "  Operation: new = old * old - 3\n",
+            "  Test: divisible by 2\n",
+            "    If true: throw to monkey 1\n",
+            "  Test: divisible by 3\n",
+            "    If true: throw to monkey 2\n",
+            "    If false: throw to monkey 1\n",
+            "\n",
+            "Monkey 1:\n",
+            "  Starting items: 7\n",
+            "  Operation: new = (old + 4) * 2\n",
+            "  Test: divisible by 5\n",
+            "    If true: throw to monkey 0\n",
+            "    If false: throw to monkey 2\n",
+            "\n",
+            "Monkey 2:\n",
+            "  Starting items: 1\n",
+            "  Operation: new = old + 11 - 2\n",
+            "  Test: divisible by 7\n",
+            "    If true: throw to monkey 0\n",
+            "    If false: throw to monkey 1\n",
+        )).unwrap();
+
+        // The subtractions done modulo the LCM give the same result as the exact worry levels
+        let exact = super::simulate(&monkeys, &Simulation { rounds: 12, relief: Relief::None }).unwrap();
+        let modulo = super::simulate(&monkeys, &Simulation { rounds: 12, relief: Relief::ModuloLcm }).unwrap();
+        assert_eq!(exact, modulo);
+
+        let trace = super::trace(&monkeys, &Simulation { rounds: 1, ..Simulation::PART_1 }).unwrap();
+        assert!(trace.render(TraceFormat::Text).starts_with(concat!(
+            "== Round 1 ==\n",
+            "Monkey 0:\n",
+            "  Monkey inspects an item with a worry level of 10.\n",
+            "    Worry level is set to old * old - 3 = 97.\n",
+            "    Monkey gets bored with item. Worry level is divided by 3 to 32.\n",
+            "    Current worry level is divisible by 2.\n",
+            "    Item with worry level 32 is thrown to monkey 1.\n",
+            "  Monkey inspects an item with a worry level of 20.\n",
+            "    Worry level is set to old * old - 3 = 397.\n",
+            "    Monkey gets bored with item. Worry level is divided by 3 to 132.\n",
+            "    Current worry level is divisible by 2.\n",
+        )));
+    }
+
+    #[test]
+    fn test_invalid_targets() {
+        let monkey = |id, if_true, if_false| format!(concat!(
+            "Monkey {}:\n",
+            "  Starting items: 79, 98\n",
+            "  Operation: new = old * 19\n",
+            "  Test: divisible by 23\n",
+            "    If true: throw to monkey {}\n",
+            "    If false: throw to monkey {}\n",
+        ), id, if_true, if_false);
+
+        let input = monkey(0, 1, 0) + "\n" + &monkey(1, 0, 0);
+        assert_eq!(
+            Day11::parse(&input).err().unwrap().to_string(),
+            "invalid input: monkey 0 throws items to itself"
+        );
+        let input = monkey(0, 1, 1) + "\n" + &monkey(1, 0, 2);
+        assert_eq!(
+            Day11::parse(&input).err().unwrap().to_string(),
+            "invalid input: monkey 1 throws items to monkey 2, which does not exist"
+        );
+        assert!(Day11::parse(&(monkey(0, 1, 1) + "\n" + &monkey(1, 0, 0))).is_ok());
+    }
+
+}
@@ -2,6 +2,10 @@ use std::path::Path;
 use std::fs;
 use std::cmp::Ordering;
 
+use crate::error::Result;
+use crate::parse;
+use crate::solution::{Answer, Solution};
+
 
 
 #[derive(Debug, PartialEq, Eq)]
@@ -60,10 +64,13 @@ pub struct Signal (Vec<PacketPair>);
 
 impl Signal {
 
-    pub fn from_file<P: AsRef<Path>>(filename: P) -> Self {
-        let input = fs::read_to_string(filename).unwrap();
-        let (_, signal) = parser::parse_signal(&input).unwrap();
-        signal
+    pub fn from_file<P: AsRef<Path>>(filename: P) -> Result<Self> {
+        let input = fs::read_to_string(filename)?;
+        Self::parse(&input)
+    }
+
+    pub fn parse(input: &str) -> Result<Self> {
+        parse::finish(input, parser::parse_signal)
     }
 
     pub fn count_pairs_in_right_order(&self) -> u32 {
@@ -87,13 +94,13 @@ impl Signal {
                 Value::Integer(2)
             )))
         )));
-        v.push(&marker1);
+        v.push(marker1);
         let marker2 = &Value::List(Box::new(vec!(
             Value::List(Box::new(vec!(
                 Value::Integer(6)
             )))
         )));
-        v.push(&marker2);
+        v.push(marker2);
 
         v.sort();
 
@@ -117,11 +124,12 @@ mod parser {
     use nom::multi::{separated_list0, separated_list1};
     use nom::branch::alt;
     use nom::sequence::{delimited, separated_pair};
-    use nom::combinator::map;
+    use nom::combinator::{map, opt};
+    use nom::sequence::terminated;
 
     fn parse_value(input: &str) -> IResult<&str, Value> {
         alt((
-            map(u32, |v| Value::Integer(v)),
+            map(u32, Value::Integer),
             parse_list,
         ))(input)
     }
@@ -150,11 +158,14 @@ mod parser {
 
     pub fn parse_signal(input: &str) -> IResult<&str, Signal> {
         map(
-            separated_list1(
-                multispace1,
-                parse_packet_pair
+            terminated(
+                separated_list1(
+                    multispace1,
+                    parse_packet_pair
+                ),
+                opt(line_ending)
             ),
-            |v| Signal(v)
+            Signal
         )(input)
     }
 
@@ -172,7 +183,7 @@ mod parser {
 
             assert_eq!(
                 super::parse_value("[]"),
-                Ok(("", Value::List(Box::new(vec!()))))
+                Ok(("", Value::List(Box::default())))
             );
 
             assert_eq!(
@@ -199,15 +210,35 @@ mod parser {
 
 
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u32 {
-    let signal = Signal::from_file(filename);
-    signal.count_pairs_in_right_order()
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    type Input = Signal;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Signal::parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(input.count_pairs_in_right_order().into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(input.calculate_part_2().into())
+    }
+}
+
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let signal = Signal::from_file(filename)?;
+    Ok(signal.count_pairs_in_right_order())
 }
 
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> u32 {
-    let signal = Signal::from_file(filename);
-    signal.calculate_part_2()
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let signal = Signal::from_file(filename)?;
+    Ok(signal.calculate_part_2())
 }
 
 
@@ -216,13 +247,13 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        let signal = super::Signal::from_file("test_input/day13.txt");
+        let signal = super::Signal::from_file("test_input/day13.txt").unwrap();
         assert_eq!(signal.count_pairs_in_right_order(), 13);
     }
 
     #[test]
     fn test_part_2() {
-        let signal = super::Signal::from_file("test_input/day13.txt");
+        let signal = super::Signal::from_file("test_input/day13.txt").unwrap();
         assert_eq!(signal.calculate_part_2(), 140);
     }
 
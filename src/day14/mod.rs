@@ -4,20 +4,19 @@ use std::path::Path;
 use std::fs;
 use std::cmp::{min, max};
 use itertools::Itertools;
-use std::ops::{Index, IndexMut};
 use std::fmt;
 
+use crate::error::{AocError, Result};
+use crate::geometry::{BoundingBox, Point};
+use crate::grid::Grid;
+use crate::parse;
+use crate::solution::{Answer, Solution};
 
-#[derive(Debug, PartialEq)]
-pub struct Position{ x: usize, y: usize}
 
-#[derive(Debug)]
-pub struct InputData (
-    Vec<Vec<Position>>,
-);
+type Position = Point<usize>;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum CellContent {
+pub enum CellContent {
     Air,
     Wall,
     Sand,
@@ -25,7 +24,7 @@ enum CellContent {
 
 
 impl CellContent {
-    pub fn to_char(&self) -> char {
+    pub fn to_char(self) -> char {
         match self {
             Self::Air => ' ',
             Self::Wall => '#',
@@ -41,126 +40,113 @@ impl fmt::Display for CellContent {
 }
 
 
-#[derive(Default)]
-struct World {
-    xmin: usize,
-    xmax: usize,
-    row_size: usize,
+// The cave, from y = 0 to ymax (the floor)
+#[derive(Clone)]
+pub struct World {
     ymax: usize,
-    cells: Box<[CellContent]>,
-}
-
-
-impl Index<(usize, usize)> for World {
-    type Output = CellContent;
-    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
-        &self.cells[self.from_coords_to_indice(x, y)]
-    }
-}
-
-impl IndexMut<(usize, usize)> for World {
-    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
-        &mut self.cells[self.from_coords_to_indice(x, y)]
-    }
+    cells: Grid<CellContent>,
 }
 
 
 impl World {
 
-    #[inline]
-    fn from_coords_to_indice(&self, x: usize, y: usize) -> usize {
-        y * self.row_size + (x - self.xmin)
-    }
-
     // Create an empty world (filled of CellContent::Air) of a given size
     fn create_empty_world(xmin: usize, xmax: usize, ymax: usize) -> Self {
-        let cells_size: usize = (xmax - xmin + 1) * (ymax + 1 + 2);
         let mut w = World {
-            xmin,
-            xmax,
-            row_size: xmax - xmin + 1,
             ymax,
-            cells: vec!(CellContent::Air; cells_size).into_boxed_slice(),
+            cells: Grid::with_origin((xmin, 0), xmax - xmin + 1, ymax + 1, CellContent::Air),
         };
 
         // Bottom wall
         for x in xmin..=xmax {
-            w[(x, ymax)] = CellContent::Wall;
+            w.cells[(x, ymax)] = CellContent::Wall;
         };
 
         w
     }
 
-    fn set_walls(&mut self, walls: &Vec<Vec<Position>>) {
+    fn set_walls(&mut self, walls: &Vec<Vec<Position>>) -> Result<()> {
         for wall in walls {
+            for p in wall {
+                if !self.cells.contains((p.x, p.y)) || p.y >= self.ymax {
+                    return Err(AocError::invalid(format!("wall point {},{} is outside of the cave", p.x, p.y)));
+                }
+            }
             for (p1, p2) in wall.iter().tuple_windows::<(_, _)>() {
                 if p1.x == p2.x {
                     for y in min(p1.y, p2.y)..=max(p1.y, p2.y) {
-                        self[(p1.x, y)] = CellContent::Wall;
+                        self.cells[(p1.x, y)] = CellContent::Wall;
                     }
                 }
                 else if p1.y == p2.y {
                     for x in min(p1.x, p2.x)..=max(p1.x, p2.x) {
-                        self[(x, p1.y)] = CellContent::Wall;
+                        self.cells[(x, p1.y)] = CellContent::Wall;
                     }
                 }
                 else {
-                    panic!("Invalid segment");
+                    return Err(AocError::invalid(format!(
+                        "invalid segment from {},{} to {},{}: walls must be horizontal or vertical",
+                        p1.x, p1.y, p2.x, p2.y
+                    )));
                 }
                 //print!("{}", self);
             }
         }
+        Ok(())
+    }
+
+    pub fn from_file<P: AsRef<Path>>(filename: P) -> Result<Self> {
+        let input = fs::read_to_string(filename)?;
+        Self::parse(&input)
     }
 
-    pub fn from_file<P: AsRef<Path>>(filename: P) -> Self {
-        let input = fs::read_to_string(filename).unwrap();
-        let (_, walls) = parser::parse_world(&input).unwrap();
+    pub fn parse(input: &str) -> Result<Self> {
+        let walls = parse::finish(input, parser::parse_world)?;
 
         // Get the height of the world and use it to calculate the boundaries
-        let mut ymax = usize::MIN;
-        walls.iter().flatten().for_each(|Position{ x:_, y }| {
-            ymax = max(ymax, *y);
-        });
-        ymax = ymax + 2;
-        let xmin: usize = 500 - (ymax - 1);
+        let walls_bbox = BoundingBox::from_points(walls.iter().flatten().copied())
+            .ok_or_else(|| AocError::invalid("the cave has no walls"))?;
+        let ymax = walls_bbox.max.y + 2;
+        let xmin: usize = 500usize.checked_sub(ymax - 1)
+            .ok_or_else(|| AocError::invalid(format!("the cave is too deep ({ymax})")))?;
         let xmax: usize = 500 + (ymax + 1);
 
         let mut w = Self::create_empty_world(xmin, xmax, ymax);
 
-        w.set_walls(&walls);
+        w.set_walls(&walls)?;
 
-        w
+        Ok(w)
     }
 
 
     fn drop_sand(&mut self, pos: Position) -> bool {
 
         let mut y = pos.y;
-        while (y < self.ymax) && (self[(pos.x, y + 1)] == CellContent::Air) {
+        while (y < self.ymax) && (self.cells[(pos.x, y + 1)] == CellContent::Air) {
             y += 1;
         }
         if y == self.ymax - 1 {
-            self[(pos.x, y)] = CellContent::Sand;
+            self.cells[(pos.x, y)] = CellContent::Sand;
             return true;
         }
 
         // to the left
-        if pos.x == self.xmin {
+        if pos.x == self.cells.x_range().start {
             return true;
         }
-        if self[(pos.x - 1, y + 1)] == CellContent::Air {
+        if self.cells[(pos.x - 1, y + 1)] == CellContent::Air {
             return self.drop_sand(Position{ x: pos.x-1, y: y+1 });
         }
         
         // to the right
-        if pos.x == self.xmax {
+        if pos.x == self.cells.x_range().end - 1 {
             return true;
         }
-        if self[(pos.x + 1, y + 1)] == CellContent::Air {
+        if self.cells[(pos.x + 1, y + 1)] == CellContent::Air {
             return self.drop_sand(Position{ x: pos.x+1, y: y+1 });
         }
 
-        self[(pos.x, y)] = CellContent::Sand;
+        self.cells[(pos.x, y)] = CellContent::Sand;
         false
     }
 
@@ -174,7 +160,7 @@ impl World {
 
     pub fn run_part_2(&mut self) -> u32 {
         let mut count = 0u32;
-        while self[(500, 0)] == CellContent::Air {
+        while self.cells[(500, 0)] == CellContent::Air {
             self.drop_sand(Position{ x: 500, y: 0 });
             count += 1;
         }
@@ -186,14 +172,7 @@ impl World {
 
 impl fmt::Display for World {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut output = String::with_capacity((self.row_size + 1) * (self.ymax + 1));
-        for y in 0..=self.ymax {
-            for x in self.xmin..=self.xmax {
-                output.push(self[(x, y)].to_char());
-            }
-            output.push('\n')
-        }
-        writeln!(f, "{}", output)
+        writeln!(f, "{}", self.cells.render(|cell| cell.to_char()))
     }
 }
 
@@ -203,13 +182,14 @@ impl fmt::Display for World {
 mod parser {
 
     use super::Position;
+    use crate::parse::lines;
 
     use nom::IResult;
     use nom::bytes::complete::tag;
-    use nom::character::complete::{char, u32, newline};
+    use nom::character::complete::{char, u32};
     use nom::sequence::separated_pair;
     use nom::multi::separated_list1;
-    use nom::combinator::{map, complete};
+    use nom::combinator::map;
 
     fn parse_position(input: &str) -> IResult<&str, Position> {
         map(
@@ -230,12 +210,7 @@ mod parser {
     }
 
     pub fn parse_world(input: &str) -> IResult<&str, Vec<Vec<Position>>> {
-        complete(
-            separated_list1(
-                newline,
-                parse_wall
-            )
-        )(input)
+        lines(parse_wall)(input)
     }
 
     #[cfg(test)]
@@ -246,7 +221,7 @@ mod parser {
         fn test_parse_world(){
             assert_eq!(
                 super::parse_world("498,4 -> 498,6 -> 496,6\n503,4 -> 502,4 -> 502,9 -> 494,9\n"),
-                Ok(("\n",
+                Ok(("",
                     vec![
                         vec![Position{x:498, y:4}, Position{x:498, y:6}, Position{x:496, y:6}],
                         vec![Position{x:503, y:4}, Position{x:502, y:4}, Position{x:502, y:9}, Position{x:494, y:9}]
@@ -258,14 +233,34 @@ mod parser {
 }
 
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u32 {
-    let mut w = World::from_file(filename);
-    w.run_part_1()
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    type Input = World;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        World::parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(input.clone().run_part_1().into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(input.clone().run_part_2().into())
+    }
 }
 
-pub fn run_part_2<P: AsRef<Path>>(filename: P) -> u32 {
-    let mut w = World::from_file(filename);
-    w.run_part_2()
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let mut w = World::from_file(filename)?;
+    Ok(w.run_part_1())
+}
+
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u32> {
+    let mut w = World::from_file(filename)?;
+    Ok(w.run_part_2())
 }
 
 
@@ -277,15 +272,22 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        let mut w = super::World::from_file("test_input/day14.txt");
+        let mut w = super::World::from_file("test_input/day14.txt").unwrap();
         assert_eq!(w.run_part_1(), 24);
     }
 
     #[test]
     fn test_part_2() {
-        let mut w = super::World::from_file("test_input/day14.txt");
+        let mut w = super::World::from_file("test_input/day14.txt").unwrap();
         assert_eq!(w.run_part_2(), 93);
     }
 
-}
+    #[test]
+    fn test_invalid_segment() {
+        assert_eq!(
+            super::World::parse("498,4 -> 497,6\n").err().unwrap().to_string(),
+            "invalid input: invalid segment from 498,4 to 497,6: walls must be horizontal or vertical"
+        );
+    }
 
+}
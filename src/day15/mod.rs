@@ -2,19 +2,23 @@
 use std::path::Path;
 use std::fs;
 use std::collections::HashSet;
+use std::fmt::Write;
+use std::str::FromStr;
+use std::num::NonZero;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::thread;
 
+use itertools::Itertools;
 
-#[derive(Debug, PartialEq)]
-struct Position {
-    x: i64,
-    y: i64,
-}
+use crate::error::{AocError, Result};
+use crate::geometry::{BoundingBox, Point};
+use crate::interval::IntervalSet;
+use crate::parse;
+use crate::solution::{Answer, ParsedPuzzle, Puzzle, Solution};
 
-impl Position {
-    fn get_distance(&self, other: &Self) -> u64 {
-        (self.x - other.x).unsigned_abs() + (self.y - other.y).unsigned_abs()
-    }
-}
+
+type Position = Point<i64>;
 
 
 #[derive(Debug, PartialEq)]
@@ -28,98 +32,226 @@ pub struct Input {
     sensors: Vec<Sensor>,
 }
 
+/// How part 2 looks for the position which no sensor covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Merge the ranges covered by the sensors in each row, the rows being shared between
+    /// `threads` threads (at least one)
+    RowScan { threads: usize },
+    /// Only check where the lines just outside of the sensor ranges cross
+    Boundaries,
+}
 
-mod range {
-    use std::cmp::{min, max};
+impl FromStr for Strategy {
+    type Err = AocError;
 
-    type Range = (i64, i64);
+    // `rows` alone uses one thread per core
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            None if s == "boundaries" => Ok(Self::Boundaries),
+            None if s == "rows" => {
+                let threads = thread::available_parallelism().map_or(1, NonZero::get);
+                Ok(Self::RowScan { threads })
+            }
+            Some(("rows", threads)) => match threads.parse::<usize>() {
+                Ok(threads) if threads > 0 => Ok(Self::RowScan { threads }),
+                _ => Err(AocError::invalid(format!("invalid thread count '{threads}'"))),
+            },
+            _ => Err(AocError::invalid(format!("unknown strategy '{s}', expected boundaries, rows or rows:<threads>"))),
+        }
+    }
+}
 
-    pub const EMPTY: (i64, i64) = (0, -1);
 
-    pub fn overlaps(r1: &Range, r2: &Range) -> bool {
-        // https://stackoverflow.com/questions/3269434/whats-the-most-efficient-way-to-test-if-two-ranges-overlap
-        (r1.0 <= r2.1) && (r2.0 <= r1.1)
-    }
+impl Sensor {
 
-    pub fn merge(dest: &mut Range, source: &Range) {
-        dest.0 = min(dest.0, source.0);
-        dest.1 = max(dest.1, source.1);
+    pub fn position(&self) -> Position {
+        self.position
     }
 
-    pub fn get_size(r: &Range) -> u64 {
-        (r.1 - r.0 + 1) as u64
+    pub fn nearest_beacon(&self) -> Position {
+        self.nearest_beacon
     }
 
-    #[cfg(test)]
-    mod test {
-
-        #[test]
-        fn test_overlaps() {
-            assert!(super::overlaps(&(1, 2), &(1, 2)));
-            assert!(super::overlaps(&(1, 4), &(4, 8)));
-            assert!(super::overlaps(&(10, 20), &(-10, 40)));
-            assert!(super::overlaps(&(-10, 40), &(10, 20)));
-
-            assert!(!super::overlaps(&(1, 4), &(5, 8)));
-            assert!(!super::overlaps(&(-10, -44), &(4, 8)));
-        }
-
-        #[test]
-        fn test_merge() {
-            todo!();
-        }
+    /// Distance up to which the sensor rules out other beacons.
+    pub fn radius(&self) -> u64 {
+        self.position.manhattan(&self.nearest_beacon)
     }
 
+    pub fn covers(&self, p: &Position) -> bool {
+        self.position.manhattan(p) <= self.radius()
+    }
 }
 
 
 impl Input {
 
-    pub fn from_file<P: AsRef<Path>>(filename: P) -> Self {
-        let input_string = fs::read_to_string(filename).unwrap();
-        let (_, input) = parser::parse_input(&input_string).unwrap();
-        input
+    pub fn from_file<P: AsRef<Path>>(filename: P) -> Result<Self> {
+        let input_string = fs::read_to_string(filename)?;
+        Self::parse(&input_string)
+    }
+
+    pub fn parse(input_string: &str) -> Result<Self> {
+        parse::finish(input_string, parser::parse_input)
     }
 
     pub fn run_part_1(&self, y_check: i64) -> u64 {
-        let mut ranges: Vec<(i64, i64)> = Vec::new();
-        let mut beacons_in_checked_row: HashSet<i64> = HashSet::new();
-        
-        for sensor in &self.sensors {
-            if let Some(range) = Self::get_range_in_row_x(&sensor.position, &sensor.nearest_beacon, y_check) {
-                ranges.push(range);
-                let beacon_position = &sensor.nearest_beacon;
-                if beacon_position.y == y_check {
-                    beacons_in_checked_row.insert(beacon_position.x);
-                }
-            }
-        }
+        let coverage = self.coverage_of_row(y_check);
+        let beacons_in_checked_row: HashSet<i64> = self.sensors.iter()
+            .map(|sensor| &sensor.nearest_beacon)
+            .filter(|beacon_position| beacon_position.y == y_check)
+            .map(|beacon_position| beacon_position.x)
+            .collect();
+
+        // The beacons are in the covered positions, since they are at the range of their sensor
+        coverage.len() - beacons_in_checked_row.len() as u64
+    }
 
-        // sort ranges by starting position
-        ranges.sort_by( |r1, r2| r1.0.cmp(&r2.0) );
+    /// Tuning frequency of the only position of the `0..=max` square which no sensor covers.
+    pub fn run_part_2(&self, max: i64, strategy: Strategy) -> Result<u64> {
+        let found = match strategy {
+            Strategy::RowScan { threads } => self.find_uncovered_by_rows(max, threads),
+            Strategy::Boundaries => self.find_uncovered_on_boundaries(max),
+        };
+        let p = found.ok_or_else(|| AocError::invalid(format!("no position of the square 0..={max} is uncovered")))?;
+        // The position is in the square, so its coordinates are not negative
+        (p.x as u64).checked_mul(4000000)
+            .and_then(|frequency| frequency.checked_add(p.y as u64))
+            .ok_or_else(|| AocError::invalid(format!("the tuning frequency of ({}, {}) overflows u64", p.x, p.y)))
+    }
 
-        // Merge ranges when they overlap and count the total size
-        let mut current_range = range::EMPTY;
-        let positions_with_no_beacon = ranges.iter().fold(0u64, |acc, r| {
-            if current_range == range::EMPTY {
-                current_range = *r;
-                return range::get_size(r);
-            }
-            if range::overlaps(&current_range, r) {
-                let size_before = range::get_size(&current_range);
-                range::merge(&mut current_range, &r);
-                return acc - size_before + range::get_size(&current_range);
+    // The threads take the rows by blocks, in order. The uncovered position of the lowest row is
+    // returned whatever the order in which the threads find them.
+    //
+    // The blocks are counted rather than their first rows, so that the cursor cannot overflow
+    // when `max` is close to `i64::MAX`.
+    fn find_uncovered_by_rows(&self, max: i64, threads: usize) -> Option<Position> {
+        let threads = threads.max(1);
+        // Small enough for each thread to get many blocks
+        let block_size = (max.saturating_add(1) / (threads as i64).saturating_mul(64)).clamp(1, 1000);
+        let next_block = AtomicI64::new(0);
+        let found: Mutex<Option<Position>> = Mutex::new(None);
+
+        thread::scope(|scope| {
+            for _ in 0..threads {
+                scope.spawn(|| loop {
+                    let block = next_block.fetch_add(1, Ordering::Relaxed);
+                    let Some(start) = block.checked_mul(block_size).filter(|&start| start <= max) else {
+                        break;
+                    };
+                    // The blocks before this one have all been taken already
+                    if found.lock().unwrap().is_some_and(|p| p.y < start) {
+                        break;
+                    }
+                    let end = start.saturating_add(block_size - 1).min(max);
+                    if let Some(p) = (start..=end).find_map(|y| self.find_uncovered_in_row(y, max)) {
+                        let mut found = found.lock().unwrap();
+                        if found.is_none_or(|f| p.y < f.y) {
+                            *found = Some(p);
+                        }
+                        break;
+                    }
+                });
             }
-            current_range = *r;
-            acc + range::get_size(r)
         });
+        found.into_inner().unwrap()
+    }
+
+    fn find_uncovered_in_row(&self, y: i64, max: i64) -> Option<Position> {
+        let coverage = self.coverage_of_row(y);
+        if coverage.contains_range(&(0..=max)) {
+            return None;
+        }
+        let x = *coverage.complement(0..=max).iter().next().unwrap().start();
+        Some(Position::new(x, y))
+    }
+
+    // The neighbours of the uncovered position are covered, so it is just outside of the range of
+    // several sensors: where their borders cross, or where one of them meets the square.
+    //
+    // With u = x + y and v = x - y, the border of a sensor is made of two lines of constant u and
+    // two lines of constant v, and the candidates are where these lines cross.
+    fn find_uncovered_on_boundaries(&self, max: i64) -> Option<Position> {
+        let mut u_lines = Vec::new();
+        let mut v_lines = Vec::new();
+        for sensor in &self.sensors {
+            let (p, d) = (sensor.position, sensor.radius() as i64 + 1);
+            u_lines.extend([p.x + p.y - d, p.x + p.y + d]);
+            v_lines.extend([p.x - p.y - d, p.x - p.y + d]);
+        }
+        u_lines.sort_unstable();
+        u_lines.dedup();
+        v_lines.sort_unstable();
+        v_lines.dedup();
+
+        // Lines of u and v of different parities cross between positions
+        let crossings = u_lines.iter().cartesian_product(&v_lines)
+            .filter(|(u, v)| (*u - *v) % 2 == 0)
+            .map(|(u, v)| Position::new((u + v) / 2, (u - v) / 2));
+        let on_sides = u_lines.iter()
+            .flat_map(|&u| [(0, u), (max, u - max), (u, 0), (u - max, max)])
+            .chain(v_lines.iter().flat_map(|&v| [(0, -v), (max, max - v), (v, 0), (v + max, max)]))
+            .map(Position::from);
+        let corners = [(0, 0), (max, 0), (0, max), (max, max)].map(Position::from);
+
+        let square = BoundingBox { min: Position::new(0, 0), max: Position::new(max, max) };
+        crossings.chain(on_sides).chain(corners)
+            .find(|p| square.contains(*p) && !self.is_covered(p))
+    }
+
+    pub fn sensors(&self) -> &[Sensor] {
+        &self.sensors
+    }
+
+    /// The sensors in range of `p`. A position covered by a sensor cannot hold an unknown beacon.
+    pub fn sensors_covering(&self, p: &Position) -> Vec<&Sensor> {
+        self.sensors.iter().filter(|sensor| sensor.covers(p)).collect()
+    }
+
+    pub fn is_covered(&self, p: &Position) -> bool {
+        self.sensors.iter().any(|sensor| sensor.covers(p))
+    }
 
-        positions_with_no_beacon - beacons_in_checked_row.len() as u64
+    /// The x of the positions of row `y` covered by a sensor, the known beacons included.
+    pub fn coverage_of_row(&self, y: i64) -> IntervalSet<i64> {
+        self.sensors.iter()
+            .filter_map(|sensor| Self::get_range_in_row_x(&sensor.position, &sensor.nearest_beacon, y))
+            .map(|(start, end)| start..=end)
+            .collect()
+    }
+
+    /// The y of the positions of column `x` covered by a sensor, the known beacons included.
+    pub fn coverage_of_column(&self, x: i64) -> IntervalSet<i64> {
+        // A column is a row once x and y are swapped
+        let transpose = |p: &Position| Position::new(p.y, p.x);
+        self.sensors.iter()
+            .filter_map(|sensor| Self::get_range_in_row_x(&transpose(&sensor.position), &transpose(&sensor.nearest_beacon), x))
+            .map(|(start, end)| start..=end)
+            .collect()
+    }
+
+    /// Number of positions of `area` covered by a sensor.
+    pub fn covered_area(&self, area: &BoundingBox<i64>) -> u64 {
+        let columns = IntervalSet::from(area.min.x..=area.max.x);
+        (area.min.y..=area.max.y)
+            .map(|y| self.coverage_of_row(y).intersection(&columns).len())
+            .sum()
+    }
+
+    /// The positions of `area` covered by no sensor, row by row.
+    pub fn uncovered_positions(&self, area: BoundingBox<i64>) -> impl Iterator<Item = Position> + '_ {
+        (area.min.y..=area.max.y).flat_map(move |y| {
+            let uncovered = self.coverage_of_row(y).complement(area.min.x..=area.max.x);
+            uncovered.iter()
+                .flat_map(|range| range.clone())
+                .map(|x| Position::new(x, y))
+                .collect::<Vec<_>>()
+        })
     }
 
     fn get_range_in_row_x(sensor_position: &Position, beacon_position: &Position, row_of_range: i64) -> Option<(i64, i64)> {
         //distance between sensor and beacon
-        let distance = sensor_position.get_distance(beacon_position);
+        let distance = sensor_position.manhattan(beacon_position);
 
         // vertical distance between the sensor and the row to check
         let diff = sensor_position.y.abs_diff(row_of_range);
@@ -135,43 +267,208 @@ impl Input {
     }
 }
 
+/// Output format of `Input::render`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderFormat {
+    /// Diagram like the ones of the puzzle, with the coordinates along the sides
+    #[default]
+    Text,
+    /// Binary grayscale image, the brighter the more positions are covered
+    Pgm,
+    /// Binary color image: the grayscale one, with the sensors in red, the beacons in blue and
+    /// the cells holding an uncovered position in yellow
+    Ppm,
+}
+
+impl FromStr for RenderFormat {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Self::Text),
+            "pgm" => Ok(Self::Pgm),
+            "ppm" => Ok(Self::Ppm),
+            _ => Err(AocError::invalid(format!("unknown format '{s}', expected text, pgm or ppm"))),
+        }
+    }
+}
+
+// What a cell of the rendering, made of up to scale x scale positions, contains
+#[derive(Debug, Clone, Copy, Default)]
+struct Cell {
+    positions: u64,
+    covered: u64,
+    sensor: bool,
+    beacon: bool,
+}
+
+impl Cell {
+    fn to_char(self) -> char {
+        if self.sensor {
+            'S'
+        } else if self.beacon {
+            'B'
+        } else if self.covered < self.positions {
+            '.'
+        } else {
+            '#'
+        }
+    }
+
+    fn gray(self) -> u8 {
+        (self.covered * 255 / self.positions) as u8
+    }
+
+    fn color(self) -> [u8; 3] {
+        if self.sensor {
+            [255, 0, 0]
+        } else if self.beacon {
+            [0, 0, 255]
+        } else if self.covered < self.positions {
+            [255, 255, 0]
+        } else {
+            [self.gray(); 3]
+        }
+    }
+}
+
+impl Input {
+
+    /// Draw the positions of `window`, each character or pixel standing for a square of
+    /// `scale` x `scale` positions.
+    pub fn render(&self, window: &BoundingBox<i64>, scale: u64, format: RenderFormat) -> Result<Vec<u8>> {
+        if scale == 0 {
+            return Err(AocError::invalid("the scale must be at least 1"));
+        }
+        let cells = self.rasterize(window, scale);
+        let (width, height) = (cells[0].len(), cells.len());
+        let output = match format {
+            RenderFormat::Text => Self::render_text(window, scale, &cells).into_bytes(),
+            RenderFormat::Pgm => {
+                let mut image = format!("P5\n{width} {height}\n255\n").into_bytes();
+                image.extend(cells.iter().flatten().map(|cell| cell.gray()));
+                image
+            }
+            RenderFormat::Ppm => {
+                let mut image = format!("P6\n{width} {height}\n255\n").into_bytes();
+                image.extend(cells.iter().flatten().flat_map(|cell| cell.color()));
+                image
+            }
+        };
+        Ok(output)
+    }
+
+    fn render_text(window: &BoundingBox<i64>, scale: u64, cells: &[Vec<Cell>]) -> String {
+        let scale = scale as i64;
+        let x_of = |column: usize| window.min.x + column as i64 * scale;
+        let y_of = |row: usize| window.min.y + row as i64 * scale;
+
+        // The columns at a multiple of 5 cells are numbered, one digit per line
+        let labels: Vec<(usize, String)> = (0..cells[0].len())
+            .filter(|&column| x_of(column).rem_euclid(5 * scale) == 0)
+            .map(|column| (column, x_of(column).to_string()))
+            .collect();
+        let label_height = labels.iter().map(|(_, label)| label.len()).max().unwrap_or(0);
+        let margin = (0..cells.len()).map(|row| y_of(row).to_string().len()).max().unwrap() + 1;
+
+        let mut s = String::new();
+        for line in 0..label_height {
+            let mut header = vec![' '; margin + cells[0].len()];
+            for (column, label) in &labels {
+                if let Some(c) = label.chars().rev().nth(label_height - 1 - line) {
+                    header[margin + column] = c;
+                }
+            }
+            s.push_str(header.iter().collect::<String>().trim_end());
+            s.push('\n');
+        }
+        for (row, cells) in cells.iter().enumerate() {
+            write!(s, "{:>width$} ", y_of(row), width = margin - 1).unwrap();
+            s.extend(cells.iter().map(|cell| cell.to_char()));
+            s.push('\n');
+        }
+        s
+    }
+
+    // The cells of the window, row by row
+    fn rasterize(&self, window: &BoundingBox<i64>, scale: u64) -> Vec<Vec<Cell>> {
+        let width = window.width().div_ceil(scale) as usize;
+        let height = window.height().div_ceil(scale) as usize;
+        let scale = scale as i64;
+        let cell_of = |x: i64| ((x - window.min.x) / scale) as usize;
+        let cell_width = |column: usize| (window.max.x - x_start(window, column, scale) + 1).min(scale) as u64;
+
+        let mut cells = Vec::with_capacity(height);
+        for row in 0..height {
+            let first_y = window.min.y + row as i64 * scale;
+            let last_y = (first_y + scale - 1).min(window.max.y);
+
+            // The cells fully covered by a range are counted in `spans`, as the difference with the
+            // previous cell, so that each row of positions takes a time which does not depend on
+            // the width of the window
+            let mut covered = vec![0u64; width];
+            let mut spans = vec![0i64; width + 1];
+            for y in first_y..=last_y {
+                for range in self.coverage_of_row(y).iter() {
+                    let (start, end) = (*range.start().max(&window.min.x), *range.end().min(&window.max.x));
+                    if start > end {
+                        continue;
+                    }
+                    let (first, last) = (cell_of(start), cell_of(end));
+                    if first == last {
+                        covered[first] += (end - start + 1) as u64;
+                        continue;
+                    }
+                    covered[first] += (x_start(window, first + 1, scale) - start) as u64;
+                    covered[last] += (end - x_start(window, last, scale) + 1) as u64;
+                    spans[first + 1] += scale;
+                    spans[last] -= scale;
+                }
+            }
+
+            let rows = (last_y - first_y + 1) as u64;
+            let mut span = 0;
+            let mut line: Vec<Cell> = (0..width)
+                .map(|column| {
+                    span += spans[column];
+                    Cell { positions: cell_width(column) * rows, covered: covered[column] + span as u64, ..Cell::default() }
+                })
+                .collect();
+            for sensor in &self.sensors {
+                if (first_y..=last_y).contains(&sensor.nearest_beacon.y) && window.contains(sensor.nearest_beacon) {
+                    line[cell_of(sensor.nearest_beacon.x)].beacon = true;
+                }
+                if (first_y..=last_y).contains(&sensor.position.y) && window.contains(sensor.position) {
+                    line[cell_of(sensor.position.x)].sensor = true;
+                }
+            }
+            cells.push(line);
+        }
+        cells
+    }
+}
+
+// x of the first position of a column of cells
+fn x_start(window: &BoundingBox<i64>, column: usize, scale: i64) -> i64 {
+    window.min.x + column as i64 * scale
+}
+
 
 
 
 mod parser {
 
     use super::{Position, Sensor, Input};
+    use crate::parse::{lines, xy};
 
     use nom::IResult;
     use nom::bytes::complete::tag;
-    use nom::character::complete::{i64, newline};
-    use nom::sequence::{preceded, terminated, separated_pair, tuple};
-    use nom::multi::many1;
+    use nom::character::complete::i64;
+    use nom::sequence::tuple;
     use nom::combinator::map;
 
-    fn parse_x(input: &str) -> IResult<&str, i64> {
-        preceded(
-            tag("x="),
-            i64
-        )(input)
-    }
-
-    fn parse_y(input: &str) -> IResult<&str, i64> {
-        preceded(
-            tag("y="),
-            i64
-        )(input)
-    }
-
     fn parse_position(input: &str) -> IResult<&str, Position> {
-        map(
-            separated_pair(
-                parse_x,
-                tag(", "),
-                parse_y
-            ),
-            |(x, y)| Position{ x, y }
-        )(input)
+        map(xy(i64), Position::from)(input)
     }
 
     fn parse_sensor(input: &str) -> IResult<&str, Sensor> {
@@ -188,12 +485,7 @@ mod parser {
 
     pub fn parse_input(input: &str) -> IResult<&str, Input> {
         map(
-            many1(
-                terminated(
-                    parse_sensor,
-                    newline
-                )
-            ),
+            lines(parse_sensor),
             |v| Input{sensors: v}
         )(input)
     }
@@ -204,12 +496,6 @@ mod parser {
 
         use super::{Position, Sensor, Input};
 
-        #[test]
-        fn test_parse_x_y() {
-            assert_eq!(super::parse_x("x=20"), Ok(("", 20i64)));
-            assert_eq!(super::parse_y("y=-9"), Ok(("", -9i64)));
-        }
-
         #[test]
         fn test_parse_position() {
             assert_eq!(
@@ -258,13 +544,60 @@ mod parser {
 }
 
 
-pub fn run_part_1<P: AsRef<Path>>(filename: P) -> u64 {
-    let input = Input::from_file(filename);
-    input.run_part_1(2000000)
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    type Input = Input;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Input::parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> Result<Answer> {
+        Ok(input.run_part_1(2000000).into())
+    }
+
+    fn part_2(input: &Self::Input) -> Result<Answer> {
+        Ok(input.run_part_2(4000000, Strategy::Boundaries)?.into())
+    }
 }
 
-pub fn run_part_2<P: AsRef<Path>>(_filename: P) -> u32 {
-    0
+
+/// Day 15 solving part 2 with the given strategy, to run or benchmark in place of `Day15`.
+pub struct Day15WithStrategy(pub Strategy);
+
+struct ParsedWithStrategy(Input, Strategy);
+
+impl Puzzle for Day15WithStrategy {
+    fn day(&self) -> u8 {
+        Day15::DAY
+    }
+
+    fn parse(&self, input: &str) -> Result<Box<dyn ParsedPuzzle>> {
+        Ok(Box::new(ParsedWithStrategy(Day15::parse(input)?, self.0)))
+    }
+}
+
+impl ParsedPuzzle for ParsedWithStrategy {
+    fn part_1(&self) -> Result<Answer> {
+        Day15::part_1(&self.0)
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        Ok(self.0.run_part_2(4000000, self.1)?.into())
+    }
+}
+
+
+pub fn run_part_1<P: AsRef<Path>>(filename: P) -> Result<u64> {
+    let input = Input::from_file(filename)?;
+    Ok(input.run_part_1(2000000))
+}
+
+pub fn run_part_2<P: AsRef<Path>>(filename: P) -> Result<u64> {
+    let input = Input::from_file(filename)?;
+    input.run_part_2(4000000, Strategy::Boundaries)
 }
 
 
@@ -275,13 +608,16 @@ pub fn run_part_2<P: AsRef<Path>>(_filename: P) -> u32 {
 #[cfg(test)]
 mod test {
 
-    use super::{Input, Position};
+    use super::{Input, Position, RenderFormat, Strategy};
+    use itertools::Itertools;
+
+    use crate::geometry::BoundingBox;
 
     #[test]
     fn test_distance_calculation() {
-        assert_eq!(Position{ x: 1, y: 1}.get_distance(&Position{ x: 3, y: 3}), 4);
-        assert_eq!(Position{ x: -2, y: -2}.get_distance(&Position{ x: 2, y: 2}), 8);
-        assert_eq!(Position{ x: 8, y: 9}.get_distance(&Position{ x: 8, y: 9}), 0);
+        assert_eq!(Position{ x: 1, y: 1}.manhattan(&Position{ x: 3, y: 3}), 4);
+        assert_eq!(Position{ x: -2, y: -2}.manhattan(&Position{ x: 2, y: 2}), 8);
+        assert_eq!(Position{ x: 8, y: 9}.manhattan(&Position{ x: 8, y: 9}), 0);
     }
 
     #[test]
@@ -298,13 +634,133 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        let input = Input::from_file("test_input/day15.txt");
+        let input = Input::from_file("test_input/day15.txt").unwrap();
         assert_eq!(input.run_part_1(10), 26);
     }
 
     #[test]
     fn test_part_2() {
-        assert_eq!(0, 1);
+        let input = Input::from_file("test_input/day15.txt").unwrap();
+        let strategies = [
+            Strategy::RowScan { threads: 1 },
+            Strategy::RowScan { threads: 4 },
+            Strategy::Boundaries,
+        ];
+        for strategy in strategies {
+            assert_eq!(input.run_part_2(20, strategy).unwrap(), 56000011);
+            assert_eq!(
+                input.run_part_2(5, strategy).unwrap_err().to_string(),
+                "invalid input: no position of the square 0..=5 is uncovered"
+            );
+        }
+    }
+
+    #[test]
+    fn test_row_scan_near_i64_max() {
+        let input = Input::parse("Sensor at x=0, y=0: closest beacon is at x=1, y=0\n").unwrap();
+        for threads in [1, 4] {
+            for max in [i64::MAX - 1, i64::MAX] {
+                assert_eq!(input.run_part_2(max, Strategy::RowScan { threads }).unwrap(), 2 * 4000000);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tuning_frequency_overflow() {
+        // The sensors cover every x up to 5e12, so the uncovered positions are too far right
+        let input = Input::parse(concat!(
+            "Sensor at x=0, y=0: closest beacon is at x=10000000000000, y=0\n",
+            "Sensor at x=0, y=10000000000000: closest beacon is at x=10000000000000, y=10000000000000\n",
+        )).unwrap();
+        assert_eq!(
+            input.run_part_2(12_000_000_000_000, Strategy::RowScan { threads: 2 }).unwrap_err().to_string(),
+            "invalid input: the tuning frequency of (10000000000001, 0) overflows u64"
+        );
+        let error = input.run_part_2(12_000_000_000_000, Strategy::Boundaries).unwrap_err().to_string();
+        assert!(error.ends_with("overflows u64"), "{error}");
+    }
+
+    #[test]
+    fn test_strategy_from_str() {
+        assert_eq!("boundaries".parse::<Strategy>().unwrap(), Strategy::Boundaries);
+        assert_eq!("rows:3".parse::<Strategy>().unwrap(), Strategy::RowScan { threads: 3 });
+        assert!(matches!("rows".parse::<Strategy>().unwrap(), Strategy::RowScan { threads } if threads > 0));
+        assert!("rows:0".parse::<Strategy>().is_err());
+        assert!("rows:x".parse::<Strategy>().is_err());
+        assert!("columns".parse::<Strategy>().is_err());
+    }
+
+    #[test]
+    fn test_parallel_row_scan() {
+        let input = Input::from_file("test_input/day15.txt").unwrap();
+
+        // Several positions are not covered in the larger square, the first one is found
+        let square = BoundingBox { min: Position::new(0, 0), max: Position::new(40, 40) };
+        let first = input.uncovered_positions(square).next().unwrap();
+        for threads in [0, 1, 3, 8] {
+            assert_eq!(
+                input.run_part_2(40, Strategy::RowScan { threads }).unwrap(),
+                first.x as u64 * 4000000 + first.y as u64
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_text() {
+        let input = Input::from_file("test_input/day15.txt").unwrap();
+        let window = BoundingBox { min: Position::new(-4, 9), max: Position::new(26, 11) };
+        let text = input.render(&window, 1, RenderFormat::Text).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), concat!(
+            "                 1    1    2    2\n",
+            "       0    5    0    5    0    5\n",
+            " 9 ...#########################...\n",
+            "10 ..####B######################..\n",
+            "11 .###S#############.###########.\n",
+        ));
+
+        // A cell is shown as uncovered if one of its positions is
+        let text = input.render(&window, 3, RenderFormat::Text).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap().lines().last().unwrap(), "9 .SB###.##..");
+    }
+
+    #[test]
+    fn test_render_images() {
+        let input = Input::from_file("test_input/day15.txt").unwrap();
+        let square = BoundingBox { min: Position::new(0, 0), max: Position::new(20, 20) };
+
+        let header = b"P5\n21 21\n255\n";
+        let image = input.render(&square, 1, RenderFormat::Pgm).unwrap();
+        assert_eq!(&image[..header.len()], header);
+        let pixels = &image[header.len()..];
+        assert_eq!(pixels.len(), 21 * 21);
+        assert_eq!(pixels.iter().positions(|&p| p != 255).collect::<Vec<_>>(), vec![11 * 21 + 14]);
+
+        // The last cells of the rows and columns only hold one position
+        let header = b"P6\n3 3\n255\n";
+        let image = input.render(&square, 10, RenderFormat::Ppm).unwrap();
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 3 * 3 * 3);
+        assert_eq!(&image[header.len() + 3 * 4..][..3], [255, 0, 0]);
+
+        assert!(input.render(&square, 0, RenderFormat::Pgm).is_err());
+    }
+
+    #[test]
+    fn test_queries() {
+        let input = Input::from_file("test_input/day15.txt").unwrap();
+        let square = BoundingBox { min: Position::new(0, 0), max: Position::new(20, 20) };
+
+        assert_eq!(input.uncovered_positions(square).collect::<Vec<_>>(), vec![Position::new(14, 11)]);
+        assert_eq!(input.covered_area(&square), 21 * 21 - 1);
+        assert!(!input.is_covered(&Position::new(14, 11)));
+
+        let sensors = input.sensors_covering(&Position::new(2, 10));
+        assert_eq!(sensors.len(), 3);
+        assert!(sensors.iter().all(|s| s.radius() >= s.position().manhattan(&Position::new(2, 10))));
+
+        let column = input.coverage_of_column(14);
+        assert_eq!(column.complement(0..=20).iter().collect::<Vec<_>>(), vec![&(11..=11)]);
+        assert_eq!(input.coverage_of_row(10).len(), 27);
     }
 
 }
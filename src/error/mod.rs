@@ -0,0 +1,141 @@
+use std::{fmt, io};
+
+
+/// Error raised when an input file cannot be read, parsed or solved.
+#[derive(Debug)]
+pub enum AocError {
+    /// The input file could not be read
+    Io(io::Error),
+    /// The input does not follow the expected format (line and column start at 1)
+    Parse { line: usize, column: usize, message: String },
+    /// The input is well-formed but its content does not make sense for the puzzle
+    Invalid(String),
+}
+
+pub type Result<T> = std::result::Result<T, AocError>;
+
+
+impl AocError {
+
+    pub fn invalid<S: Into<String>>(message: S) -> Self {
+        Self::Invalid(message.into())
+    }
+
+    /// Build a parse error located at the start of `remaining`, which must be a slice of `input`.
+    pub fn parse_error<S: Into<String>>(input: &str, remaining: &str, message: S) -> Self {
+        let (line, column) = get_line_column(input, remaining);
+        Self::Parse { line, column, message: message.into() }
+    }
+
+    /// Convert the error returned by a nom parser run on `input` (or on a slice of it).
+    pub fn from_nom(input: &str, error: nom::Err<nom::error::Error<&str>>) -> Self {
+        match error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => {
+                Self::parse_error(
+                    input,
+                    e.input,
+                    format!("unexpected input '{}' ({})", excerpt(e.input), e.code.description())
+                )
+            },
+            nom::Err::Incomplete(_) => {
+                Self::parse_error(input, &input[input.len()..], "unexpected end of input")
+            },
+        }
+    }
+
+    /// Build the error for a parser which stopped at `remaining` before the end of `input`.
+    pub fn trailing_input(input: &str, remaining: &str) -> Self {
+        let found = excerpt(remaining);
+        let message = if found.trim().is_empty() {
+            "unexpected blank line".to_string()
+        } else {
+            format!("unexpected input '{found}' after the end of the data")
+        };
+        Self::parse_error(input, remaining, message)
+    }
+}
+
+
+// Start of the first line of `s`, to show where parsing stopped
+fn excerpt(s: &str) -> String {
+    s.chars().take_while(|&c| c != '\n').take(20).collect()
+}
+
+
+// Find the line and column (starting at 1) of the start of `remaining` in `input`
+fn get_line_column(input: &str, remaining: &str) -> (usize, usize) {
+    let start = input.as_ptr() as usize;
+    let position = remaining.as_ptr() as usize;
+    let offset = if position >= start && position <= start + input.len() {
+        position - start
+    } else {
+        // Not a slice of the input, assume it is what is left at the end of it
+        input.len().saturating_sub(remaining.len())
+    };
+
+    let consumed = &input[..offset];
+    let line = consumed.matches('\n').count() + 1;
+    let line_start = consumed.rfind('\n').map_or(0, |i| i + 1);
+    let column = consumed[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::Parse { line, column, message } => {
+                write!(f, "parse error at line {line}, column {column}: {message}")
+            },
+            Self::Invalid(message) => write!(f, "invalid input: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for AocError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AocError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::AocError;
+
+    use nom::bytes::complete::tag;
+
+    #[test]
+    fn test_line_column() {
+        let input = "abc\ndef\nghi";
+        assert_eq!(super::get_line_column(input, &input[0..]), (1, 1));
+        assert_eq!(super::get_line_column(input, &input[5..]), (2, 2));
+        assert_eq!(super::get_line_column(input, &input[8..]), (3, 1));
+        assert_eq!(super::get_line_column(input, ""), (3, 4));
+    }
+
+    #[test]
+    fn test_from_nom() {
+        let input = "move 1\nmove x";
+        let line = input.lines().nth(1).unwrap();
+        let error = tag::<_, _, nom::error::Error<&str>>("move 2")(line).unwrap_err();
+        match AocError::from_nom(input, error) {
+            AocError::Parse { line, column, message } => {
+                assert_eq!((line, column), (2, 1));
+                assert_eq!(message, "unexpected input 'move x' (Tag)");
+            },
+            e => panic!("Unexpected error {e:?}"),
+        }
+    }
+}
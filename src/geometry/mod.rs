@@ -0,0 +1,258 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use crate::error::{AocError, Result};
+
+
+/// A point (or a vector) in 2D.
+///
+/// Like in `Grid`, y grows downwards: `Direction::Up` decreases y.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<Point<T>> for (T, T) {
+    fn from(p: Point<T>) -> Self {
+        (p.x, p.y)
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self { x: self.x + other.x, y: self.y + other.y }
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self { x: self.x - other.x, y: self.y - other.y }
+    }
+}
+
+impl<T: AddAssign> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Self) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Self) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self { x: -self.x, y: -self.y }
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Self;
+    fn mul(self, k: T) -> Self {
+        Self { x: self.x * k, y: self.y * k }
+    }
+}
+
+
+// Distances are returned in the unsigned type of the same width, which holds the distance along
+// one axis. Sums which do not fit in it, between points far apart, saturate at its maximum.
+macro_rules! impl_distances {
+    ($($t:ty => $u:ty),*) => {$(
+        impl Point<$t> {
+            /// Sum of the horizontal and vertical distances, saturating at the maximum of the type.
+            pub fn manhattan(&self, other: &Self) -> $u {
+                self.x.abs_diff(other.x).saturating_add(self.y.abs_diff(other.y))
+            }
+
+            /// Largest of the horizontal and vertical distances (king moves on a chessboard).
+            pub fn chebyshev(&self, other: &Self) -> $u {
+                self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+            }
+        }
+
+        impl BoundingBox<$t> {
+            /// Number of columns, saturating at the maximum of the type for a box spanning all of them.
+            pub fn width(&self) -> $u {
+                self.max.x.abs_diff(self.min.x).saturating_add(1)
+            }
+
+            /// Number of rows, saturating like `width`.
+            pub fn height(&self) -> $u {
+                self.max.y.abs_diff(self.min.y).saturating_add(1)
+            }
+        }
+    )*};
+}
+
+impl_distances!(i32 => u32, i64 => u64, isize => usize, u32 => u32, u64 => u64, usize => usize);
+
+
+/// One of the four orthogonal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All the directions, clockwise from `Up`.
+    pub const ALL: [Direction; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Left => Self::Down,
+            Self::Down => Self::Right,
+            Self::Right => Self::Up,
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        self.turn_right().turn_right()
+    }
+
+    /// Move of one step in this direction, as `(dx, dy)`.
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Self::Up => (0, -1),
+            Self::Right => (1, 0),
+            Self::Down => (0, 1),
+            Self::Left => (-1, 0),
+        }
+    }
+
+    /// Move of one step in this direction, as a vector.
+    pub fn vector<T: From<i8>>(self) -> Point<T> {
+        let (dx, dy) = self.offset();
+        Point::new(T::from(dx as i8), T::from(dy as i8))
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = AocError;
+
+    fn try_from(value: char) -> Result<Self> {
+        match value {
+            'U' => Ok(Self::Up),
+            'R' => Ok(Self::Right),
+            'D' => Ok(Self::Down),
+            'L' => Ok(Self::Left),
+            _ => Err(AocError::invalid(format!("invalid direction '{value}'"))),
+        }
+    }
+}
+
+
+/// Smallest rectangle containing a set of points, bounds included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox<T> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+}
+
+impl<T: Copy + Ord> BoundingBox<T> {
+
+    pub fn new(p: Point<T>) -> Self {
+        Self { min: p, max: p }
+    }
+
+    /// Bounding box of the points, None if there are none.
+    pub fn from_points<I: IntoIterator<Item = Point<T>>>(points: I) -> Option<Self> {
+        let mut points = points.into_iter();
+        let mut bbox = Self::new(points.next()?);
+        points.for_each(|p| bbox.include(p));
+        Some(bbox)
+    }
+
+    /// Grow the box so that it contains `p`.
+    pub fn include(&mut self, p: Point<T>) {
+        self.min = Point::new(self.min.x.min(p.x), self.min.y.min(p.y));
+        self.max = Point::new(self.max.x.max(p.x), self.max.y.max(p.y));
+    }
+
+    pub fn contains(&self, p: Point<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::{BoundingBox, Direction, Point};
+
+    #[test]
+    fn test_point_ops() {
+        let p = Point::new(3, -2);
+        assert_eq!(p + Point::new(1, 1), Point::new(4, -1));
+        assert_eq!(p - Point::new(1, 1), Point::new(2, -3));
+        assert_eq!(-p, Point::new(-3, 2));
+        assert_eq!(p * 2, Point::new(6, -4));
+        let mut q = p;
+        q += Direction::Up.vector();
+        assert_eq!(q, Point::new(3, -3));
+        assert_eq!(<(i32, i32)>::from(q), (3, -3));
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Point::new(-2i64, 5);
+        let b = Point::new(4i64, 1);
+        assert_eq!(a.manhattan(&b), 10u64);
+        assert_eq!(a.chebyshev(&b), 6u64);
+        assert_eq!(Point::new(0usize, 0).manhattan(&Point::new(2, 3)), 5usize);
+        assert_eq!(Point::new(i64::MIN, i64::MIN).manhattan(&Point::new(i64::MAX, i64::MAX)), u64::MAX);
+    }
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.turn_left(), Direction::Left);
+        assert_eq!(Direction::Left.opposite(), Direction::Right);
+        assert!(Direction::ALL.iter().all(|d| d.turn_left().turn_right() == *d));
+        assert_eq!(Direction::try_from('D').unwrap(), Direction::Down);
+        assert!(Direction::try_from('X').is_err());
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bbox = BoundingBox::from_points([Point::new(3i32, 4), Point::new(-1, 6), Point::new(2, 0)]).unwrap();
+        assert_eq!(bbox, BoundingBox { min: Point::new(-1, 0), max: Point::new(3, 6) });
+        assert_eq!((bbox.width(), bbox.height()), (5, 7));
+        assert!(bbox.contains(Point::new(0, 0)) && !bbox.contains(Point::new(4, 0)));
+        assert_eq!(BoundingBox::<i32>::from_points([]), None);
+
+        let full = BoundingBox { min: Point::new(i32::MIN, 0), max: Point::new(i32::MAX, 0) };
+        assert_eq!((full.width(), full.height()), (u32::MAX, 1));
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt;
+use std::ops::{Index, IndexMut, Range};
+
+use crate::error::{AocError, Result};
+
+
+const NEIGHBOURS_4: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+const NEIGHBOURS_8: [(isize, isize); 8] =
+    [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+
+
+/// A rectangular grid of cells stored row by row.
+///
+/// Cells are addressed by `(x, y)` with x going right and y going down. The top-left cell is at
+/// the origin, which is `(0, 0)` unless the grid is created with an offset origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    origin: (usize, usize),
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self::with_origin((0, 0), width, height, value)
+    }
+
+    /// Create a grid whose top-left cell is at `origin`.
+    pub fn with_origin(origin: (usize, usize), width: usize, height: usize, value: T) -> Self {
+        Self { origin, width, height, cells: vec![value; width * height] }
+    }
+}
+
+impl<T> Grid<T> {
+
+    /// Build a grid from its rows, which must all have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self> {
+        let width = rows.first().map_or(0, Vec::len);
+        if width == 0 {
+            return Err(AocError::invalid("the grid is empty"));
+        }
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(AocError::invalid(format!("row {y} has {} cells instead of {width}", row.len())));
+            }
+            cells.extend(row);
+        }
+        Ok(Self { origin: (0, 0), width, height, cells })
+    }
+
+    /// Parse a grid with one character per cell and one line per row.
+    ///
+    /// `cell` converts a character found at `(x, y)`; the message of its error is reported at the
+    /// line and column of the character.
+    pub fn parse<F>(input: &str, mut cell: F) -> Result<Self>
+    where
+        F: FnMut((usize, usize), char) -> std::result::Result<T, String>,
+    {
+        let mut width = None;
+        let mut cells = Vec::with_capacity(input.len());
+        let mut height = 0;
+        for (y, line) in input.lines().enumerate() {
+            let mut row_width = 0;
+            for (x, c) in line.chars().enumerate() {
+                let value = cell((x, y), c).map_err(|message| AocError::Parse {
+                    line: y + 1,
+                    column: x + 1,
+                    message,
+                })?;
+                cells.push(value);
+                row_width += 1;
+            }
+            match width {
+                None => width = Some(row_width),
+                Some(width) if width != row_width => return Err(AocError::Parse {
+                    line: y + 1,
+                    column: 1,
+                    message: format!("expected a row of {width} cells, found {row_width}"),
+                }),
+                _ => (),
+            }
+            height += 1;
+        }
+        match width {
+            Some(width) if width > 0 => Ok(Self { origin: (0, 0), width, height, cells }),
+            _ => Err(AocError::invalid("the grid is empty")),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn origin(&self) -> (usize, usize) {
+        self.origin
+    }
+
+    pub fn x_range(&self) -> Range<usize> {
+        self.origin.0..self.origin.0 + self.width
+    }
+
+    pub fn y_range(&self) -> Range<usize> {
+        self.origin.1..self.origin.1 + self.height
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        self.x_range().contains(&x) && self.y_range().contains(&y)
+    }
+
+    fn index_of(&self, (x, y): (usize, usize)) -> Option<usize> {
+        if self.contains((x, y)) {
+            Some((y - self.origin.1) * self.width + (x - self.origin.0))
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+        self.index_of(position).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, position: (usize, usize)) -> Option<&mut T> {
+        self.index_of(position).map(|i| &mut self.cells[i])
+    }
+
+    /// Positions of all the cells, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.y_range().flat_map(move |y| self.x_range().map(move |x| (x, y)))
+    }
+
+    /// All the cells with their position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> impl Iterator<Item = &T> {
+        assert!(self.y_range().contains(&y), "row {y} is outside of the grid");
+        let start = (y - self.origin.1) * self.width;
+        self.cells[start..start + self.width].iter()
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(self.x_range().contains(&x), "column {x} is outside of the grid");
+        self.cells.iter().skip(x - self.origin.0).step_by(self.width)
+    }
+
+    fn step(&self, (x, y): (usize, usize), (dx, dy): (isize, isize)) -> Option<(usize, usize)> {
+        let position = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(position).then_some(position)
+    }
+
+    /// Positions reached by walking from `start` (excluded) by steps of `(dx, dy)` until leaving
+    /// the grid.
+    pub fn ray(&self, start: (usize, usize), direction: (isize, isize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let mut current = Some(start);
+        std::iter::from_fn(move || {
+            current = self.step(current?, direction);
+            current
+        })
+    }
+
+    /// Orthogonal neighbours of a position which are inside the grid.
+    pub fn neighbours4(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBOURS_4.iter().filter_map(move |&d| self.step(position, d))
+    }
+
+    /// Orthogonal and diagonal neighbours of a position which are inside the grid.
+    pub fn neighbours8(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBOURS_8.iter().filter_map(move |&d| self.step(position, d))
+    }
+
+    /// Render the grid with one character per cell and one line per row.
+    pub fn render(&self, mut to_char: impl FnMut(&T) -> char) -> String {
+        let mut s = String::with_capacity((self.width + 1) * self.height);
+        // A grid without columns has no cells to draw
+        if self.width == 0 {
+            return s;
+        }
+        for row in self.cells.chunks(self.width) {
+            s.extend(row.iter().map(&mut to_char));
+            s.push('\n');
+        }
+        s
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        match self.index_of((x, y)) {
+            Some(i) => &self.cells[i],
+            None => panic!("position ({x}, {y}) is outside of the grid"),
+        }
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut Self::Output {
+        match self.index_of((x, y)) {
+            Some(i) => &mut self.cells[i],
+            None => panic!("position ({x}, {y}) is outside of the grid"),
+        }
+    }
+}
+
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(|&c| c))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::Grid;
+
+    fn digits(input: &str) -> Grid<u32> {
+        Grid::parse(input, |_, c| c.to_digit(10).ok_or(format!("invalid digit '{c}'"))).unwrap()
+    }
+
+    #[test]
+    fn test_parse_render() {
+        let grid = digits("123\n456\n");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.render(|d| char::from_digit(*d, 10).unwrap()), "123\n456\n");
+        assert_eq!(
+            Grid::<u32>::parse("12\n3x\n", |_, c| c.to_digit(10).ok_or(format!("invalid digit '{c}'")))
+                .unwrap_err().to_string(),
+            "parse error at line 2, column 2: invalid digit 'x'"
+        );
+        assert!(Grid::parse("12\n3\n", |_, c| Ok::<char, String>(c)).is_err());
+        assert!(Grid::parse("", |_, c| Ok::<char, String>(c)).is_err());
+        assert_eq!(Grid::new(0, 3, 'x').render(|&c| c), "");
+    }
+
+    #[test]
+    fn test_rows_columns_rays() {
+        let grid = digits("123\n456\n789\n");
+        assert_eq!(grid.row(1).copied().collect::<Vec<_>>(), vec![4, 5, 6]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), vec![3, 6, 9]);
+        assert_eq!(grid.ray((1, 1), (1, 0)).collect::<Vec<_>>(), vec![(2, 1)]);
+        assert_eq!(grid.ray((0, 0), (1, 1)).collect::<Vec<_>>(), vec![(1, 1), (2, 2)]);
+        assert_eq!(grid.ray((0, 0), (-1, 0)).count(), 0);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = digits("123\n456\n789\n");
+        assert_eq!(grid.neighbours4((0, 0)).collect::<Vec<_>>(), vec![(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbours4((1, 1)).count(), 4);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+        assert_eq!(grid.neighbours8((2, 2)).collect::<Vec<_>>(), vec![(1, 2), (1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn test_origin() {
+        let mut grid = Grid::with_origin((10, 5), 3, 2, '.');
+        grid[(12, 6)] = '#';
+        assert!(grid.contains((10, 5)) && !grid.contains((9, 5)) && !grid.contains((10, 7)));
+        assert_eq!(grid.positions().next(), Some((10, 5)));
+        assert_eq!(grid.iter().find(|(_, &c)| c == '#').map(|(p, _)| p), Some((12, 6)));
+        assert_eq!(grid.column(12).collect::<String>(), ".#");
+        assert_eq!(grid.to_string(), "...\n..#\n");
+    }
+}
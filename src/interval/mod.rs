@@ -0,0 +1,298 @@
+use std::cmp::{max, min};
+use std::ops::RangeInclusive;
+
+
+/// An integer type whose values can be counted and stepped through one by one.
+pub trait Discrete: Copy + Ord {
+    /// The value right after this one, if any.
+    fn next(self) -> Option<Self>;
+    /// The value right before this one, if any.
+    fn previous(self) -> Option<Self>;
+    /// Number of values from `start` to `end` included, `start` being at most `end`.
+    ///
+    /// Saturates at `u64::MAX` for a range over all the values of a 64-bit type.
+    fn count(start: Self, end: Self) -> u64;
+}
+
+macro_rules! impl_discrete {
+    ($($t:ty),*) => {$(
+        impl Discrete for $t {
+            fn next(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+
+            fn previous(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+
+            fn count(start: Self, end: Self) -> u64 {
+                (end.abs_diff(start) as u64).saturating_add(1)
+            }
+        }
+    )*};
+}
+
+impl_discrete!(i32, i64, u32, u64, usize);
+
+
+/// A set of integers stored as sorted inclusive ranges.
+///
+/// The ranges never overlap nor touch: inserting `3..=5` in `{1..=2}` gives `{1..=5}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<RangeInclusive<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self { ranges: Vec::new() }
+    }
+}
+
+impl<T: Discrete> From<RangeInclusive<T>> for IntervalSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut set = Self::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Discrete> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+
+/// Whether all the values of `inner` are in `outer`.
+pub fn contains<T: Ord>(outer: &RangeInclusive<T>, inner: &RangeInclusive<T>) -> bool {
+    inner.is_empty() || (outer.start() <= inner.start() && inner.end() <= outer.end())
+}
+
+/// Whether at least one value is in both ranges.
+pub fn overlaps<T: Ord>(a: &RangeInclusive<T>, b: &RangeInclusive<T>) -> bool {
+    !a.is_empty() && !b.is_empty() && a.start() <= b.end() && b.start() <= a.end()
+}
+
+
+// Whether a range ending at `end` overlaps or touches a range starting at `start`
+fn reaches<T: Discrete>(end: T, start: T) -> bool {
+    end >= start || end.next() == Some(start)
+}
+
+impl<T: Discrete> IntervalSet<T> {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The ranges of the set, sorted.
+    pub fn iter(&self) -> impl Iterator<Item = &RangeInclusive<T>> {
+        self.ranges.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of values in the set, saturating at `u64::MAX` like `Discrete::count`.
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().fold(0, |len, r| len.saturating_add(T::count(*r.start(), *r.end())))
+    }
+
+    /// Add the values of `range`, merging the ranges it overlaps or touches.
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let (start, end) = range.into_inner();
+        // The ranges from `first` to `last` (excluded) are merged with the new one
+        let first = self.ranges.partition_point(|r| !reaches(*r.end(), start));
+        let last = self.ranges.partition_point(|r| reaches(end, *r.start()));
+        let merged = if first < last {
+            min(start, *self.ranges[first].start())..=max(end, *self.ranges[last - 1].end())
+        } else {
+            start..=end
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    /// Remove the values of `range`, splitting the ranges it cuts.
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let (start, end) = range.into_inner();
+        let mut ranges = Vec::with_capacity(self.ranges.len() + 1);
+        for r in self.ranges.drain(..) {
+            if *r.end() < start || *r.start() > end {
+                ranges.push(r);
+                continue;
+            }
+            if *r.start() < start {
+                ranges.push(*r.start()..=start.previous().unwrap());
+            }
+            if *r.end() > end {
+                ranges.push(end.next().unwrap()..=*r.end());
+            }
+        }
+        self.ranges = ranges;
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.contains_range(&(value..=value))
+    }
+
+    /// Whether all the values of `range` are in the set.
+    pub fn contains_range(&self, range: &RangeInclusive<T>) -> bool {
+        range.is_empty() || self.ranges.iter().any(|r| contains(r, range))
+    }
+
+    /// Whether at least one of the values of `range` is in the set.
+    pub fn overlaps(&self, range: &RangeInclusive<T>) -> bool {
+        self.ranges.iter().any(|r| overlaps(r, range))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        for r in &other.ranges {
+            set.insert(r.clone());
+        }
+        set
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (r1, r2) = (&self.ranges[i], &other.ranges[j]);
+            let range = *max(r1.start(), r2.start())..=*min(r1.end(), r2.end());
+            if !range.is_empty() {
+                ranges.push(range);
+            }
+            // Move past the range which ends first, the other one may overlap the next range
+            if r1.end() < r2.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges }
+    }
+
+    /// The values of `bounds` which are not in the set.
+    pub fn complement(&self, bounds: RangeInclusive<T>) -> Self {
+        let mut set = Self::from(bounds);
+        for r in &self.ranges {
+            set.remove(r.clone());
+        }
+        set
+    }
+
+    /// The ranges of values missing between the first and the last value of the set.
+    pub fn gaps(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.windows(2)
+            .map(|w| w[0].end().next().unwrap()..=w[1].start().previous().unwrap())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use std::ops::RangeInclusive;
+
+    use super::IntervalSet;
+
+    fn ranges(set: &IntervalSet<i64>) -> Vec<(i64, i64)> {
+        set.iter().map(|r| (*r.start(), *r.end())).collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut set: IntervalSet<i64> = [10..=20, -5..=0, 30..=40].into_iter().collect();
+        assert_eq!(ranges(&set), vec![(-5, 0), (10, 20), (30, 40)]);
+        assert_eq!(set.len(), 6 + 11 + 11);
+
+        // Touching ranges are merged too
+        set.insert(21..=25);
+        assert_eq!(ranges(&set), vec![(-5, 0), (10, 25), (30, 40)]);
+        set.insert(15..=35);
+        assert_eq!(ranges(&set), vec![(-5, 0), (10, 40)]);
+        set.insert(RangeInclusive::new(4, 2));
+        assert_eq!(ranges(&set), vec![(-5, 0), (10, 40)]);
+        set.insert(-10..=100);
+        assert_eq!(ranges(&set), vec![(-10, 100)]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set = IntervalSet::from(0..=20);
+        set.remove(5..=9);
+        assert_eq!(ranges(&set), vec![(0, 4), (10, 20)]);
+        set.remove(-3..=0);
+        set.remove(20..=30);
+        assert_eq!(ranges(&set), vec![(1, 4), (10, 19)]);
+        set.remove(2..=15);
+        assert_eq!(ranges(&set), vec![(1, 1), (16, 19)]);
+        set.remove(0..=30);
+        assert!(set.is_empty());
+
+        let mut set = IntervalSet::from(0..=u32::MAX);
+        set.remove(0..=0);
+        set.remove(u32::MAX..=u32::MAX);
+        assert_eq!(set.len(), u32::MAX as u64 - 1);
+
+        // Counts over all the values of a 64-bit type saturate
+        assert_eq!(IntervalSet::from(0..=u64::MAX).len(), u64::MAX);
+        assert_eq!(IntervalSet::from(i64::MIN..=i64::MAX).len(), u64::MAX);
+        assert_eq!(IntervalSet::from_iter([i64::MIN..=-1, 1..=i64::MAX]).len(), u64::MAX);
+        let mut set = IntervalSet::from(0..=u64::MAX);
+        set.remove(0..=0);
+        assert_eq!(set.len(), u64::MAX);
+    }
+
+    #[test]
+    fn test_queries() {
+        let set: IntervalSet<i64> = [1..=4, 8..=10].into_iter().collect();
+        assert!(set.contains(4));
+        assert!(!set.contains(5));
+        assert!(set.contains_range(&(2..=4)));
+        assert!(!set.contains_range(&(2..=8)));
+        assert!(set.overlaps(&(4..=8)));
+        assert!(set.overlaps(&(-10..=40)));
+        assert!(!set.overlaps(&(5..=7)));
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert!(super::contains(&(2..=8), &(3..=7)));
+        assert!(super::contains(&(2..=8), &(2..=8)));
+        assert!(!super::contains(&(2..=8), &(1..=7)));
+        assert!(super::contains(&(2..=8), &RangeInclusive::new(9, 1)));
+        assert!(super::overlaps(&(5..=7), &(7..=9)));
+        assert!(!super::overlaps(&(2..=4), &(6..=8)));
+        assert!(!super::overlaps(&(2..=8), &RangeInclusive::new(5, 4)));
+    }
+
+    #[test]
+    fn test_union_intersection() {
+        let a: IntervalSet<i64> = [0..=5, 10..=15, 20..=25].into_iter().collect();
+        let b: IntervalSet<i64> = [3..=12, 14..=22].into_iter().collect();
+        assert_eq!(ranges(&a.union(&b)), vec![(0, 25)]);
+        assert_eq!(ranges(&a.intersection(&b)), vec![(3, 5), (10, 12), (14, 15), (20, 22)]);
+        assert!(a.intersection(&IntervalSet::new()).is_empty());
+    }
+
+    #[test]
+    fn test_complement_gaps() {
+        let set: IntervalSet<i64> = [-2..=3, 6..=9, 12..=30].into_iter().collect();
+        assert_eq!(ranges(&set.complement(0..=20)), vec![(4, 5), (10, 11)]);
+        assert_eq!(ranges(&set.complement(-5..=35)), vec![(-5, -3), (4, 5), (10, 11), (31, 35)]);
+        assert_eq!(set.gaps().collect::<Vec<_>>(), vec![4..=5, 10..=11]);
+    }
+}
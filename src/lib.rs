@@ -1,3 +1,12 @@
+pub mod answers;
+pub mod bench;
+pub mod error;
+pub mod geometry;
+pub mod grid;
+pub mod interval;
+pub mod parse;
+pub mod solution;
+
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -13,3 +22,45 @@ pub mod day12;
 pub mod day13;
 pub mod day14;
 pub mod day15;
+
+use solution::{register, Puzzle};
+
+
+/// All the implemented days, in order.
+pub fn registry() -> Vec<Box<dyn Puzzle>> {
+    vec![
+        register::<day01::Day01>(),
+        register::<day02::Day02>(),
+        register::<day03::Day03>(),
+        register::<day04::Day04>(),
+        register::<day05::Day05>(),
+        register::<day06::Day06>(),
+        register::<day07::Day07>(),
+        register::<day08::Day08>(),
+        register::<day09::Day09>(),
+        register::<day10::Day10>(),
+        register::<day11::Day11>(),
+        register::<day12::Day12>(),
+        register::<day13::Day13>(),
+        register::<day14::Day14>(),
+        register::<day15::Day15>(),
+    ]
+}
+
+/// Find the puzzle of a given day in the registry.
+pub fn find_puzzle(day: u8) -> Option<Box<dyn Puzzle>> {
+    registry().into_iter().find(|puzzle| puzzle.day() == day)
+}
+
+
+#[cfg(test)]
+mod test {
+
+    #[test]
+    fn test_registry() {
+        let days: Vec<u8> = super::registry().iter().map(|p| p.day()).collect();
+        assert_eq!(days, (1..=15).collect::<Vec<u8>>());
+        assert!(super::find_puzzle(7).is_some());
+        assert!(super::find_puzzle(25).is_none());
+    }
+}
@@ -1,52 +1,558 @@
-use advent_of_code_2022::{day01, day02, day03, day04, day05, day06, day07, day08, day09,
-    day10, day11, day12, day13, day14, day15};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
-fn main() {
-    // println!("Day 01 part 1: {}.", day01::run_part_1("input/day01.txt"));
-    // println!("Day 01 part 2: {}.", day01::run_part_2("input/day01.txt"));
+use advent_of_code_2022::{registry, find_puzzle};
+use advent_of_code_2022::answers::{self, Outcome};
+use advent_of_code_2022::bench::{self, Format};
+use advent_of_code_2022::day07::{self, Day07, EntryKind, FindQuery};
+use advent_of_code_2022::day11::{self, Day11, Simulation, TraceFormat};
+use advent_of_code_2022::day15::{Day15, Day15WithStrategy, RenderFormat, Strategy};
+use advent_of_code_2022::geometry::{BoundingBox, Point};
+use advent_of_code_2022::solution::{Puzzle, Solution};
 
-    // println!("Day 02 part 1: {}.", day02::run_part_1("input/day02.txt"));
-    // println!("Day 02 part 2: {}.", day02::run_part_2("input/day02.txt"));
+const USAGE: &str = "\
+Usage:
+    advent_of_code_2022 run --day <N> [--part <1|2>] [--input <path>] [--time] [--strategy <strategy>]
+    advent_of_code_2022 run --all [--part <1|2>] [--time] [--strategy <strategy>]
+    advent_of_code_2022 bench [--day <N> [--input <path>]] [--iterations <N>] [--format <text|csv|json>]
+                              [--strategy <strategy>]
+    advent_of_code_2022 verify [--day <N>] [--answers <path>]
+    advent_of_code_2022 trace [--part <1|2>] [--input <path>] [--rounds <N>] [--format <text|json>]
+    advent_of_code_2022 render [--input <path>] [--window <x1,y1,x2,y2>] [--scale <N>] [--format <text|pgm|ppm>] [--output <path>]
+    advent_of_code_2022 du [--input <path>] [--human]
+    advent_of_code_2022 tree [--input <path>] [--human]
+    advent_of_code_2022 find [--input <path>] [--human] [--name <glob>] [--type <f|d>] [--min-size <N>] [--max-size <N>]
+                             [--min-depth <N>] [--max-depth <N>]
 
-    // println!("Day 03 part 1: {}.", day03::run_part_1("input/day03.txt"));
-    // println!("Day 03 part 2: {}.", day03::run_part_2("input/day03.txt"));
+Options:
+    -d, --day <N>         Day to run
+    -p, --part <1|2>      Part to run (both parts if omitted)
+    -i, --input <path>    Input file (defaults to input/dayNN.txt)
+    -a, --all             Run every implemented day
+    -t, --time            Print the time spent parsing the input and solving each part
+    -n, --iterations <N>  Number of runs of each stage when benchmarking (default 10)
+    -f, --format <fmt>    Output of bench (text, csv or json), trace (text or json) or render (text, pgm
+                          or ppm), default text
+        --strategy <boundaries|rows[:N]>
+                          How day 15 part 2 looks for the uncovered position: crossing the sensor borders
+                          (default) or scanning the rows on N threads (default: one per core)
+        --answers <path>  File of accepted answers to verify (default answers.toml)
+    -r, --rounds <N>      Number of rounds to trace (default: all the rounds of the part)
+    -w, --window <x1,y1,x2,y2>
+                          Corners of the part of the day 15 field to render (default 0,0,4000000,4000000)
+    -s, --scale <N>       Positions per character or pixel along each side (default: fit 1000 columns)
+    -o, --output <path>   File to write the rendering to (default: the standard output)
+    -H, --human           Print the sizes of the day 07 filesystem like `du -h` (1.5K, 23M, ...)
+        --name <glob>     Names to find, `*` matching any characters and `?` a single one
+        --type <f|d>      Only find files (f) or folders (d)
+        --min-size <N>, --max-size <N>
+                          Bounds of the sizes to find, in bytes
+        --min-depth <N>, --max-depth <N>
+                          Bounds of the depths to find, the root folder being at depth 0
+    -h, --help            Print this help";
 
-    // println!("Day 04 part 1: {}.", day04::run_part_1("input/day04.txt"));
-    // println!("Day 04 part 2: {}.", day04::run_part_2("input/day04.txt"));
 
-    // println!("Day 05 part 1: {}.", day05::run_part_1("input/day05.txt"));
-    // println!("Day 05 part 2: {}.", day05::run_part_2("input/day05.txt"));
+// Value of an option, which is the next argument
+fn next_value<'a>(args: &mut impl Iterator<Item = &'a String>, option: &str) -> Result<&'a str, String> {
+    args.next().map(String::as_str).ok_or_else(|| format!("missing value for {option}"))
+}
+
+fn parse_strategy(value: &str) -> Result<Strategy, String> {
+    value.parse().map_err(|e| format!("{e}"))
+}
+
+// The strategy is only used by day 15, which must be run when one is given
+fn check_strategy(day: Option<u8>, strategy: Option<Strategy>) -> Result<(), String> {
+    match (day, strategy) {
+        (Some(day), Some(_)) if day != Day15::DAY => Err(format!("--strategy does not apply to day {day}")),
+        _ => Ok(()),
+    }
+}
+
+fn parse_part(value: &str) -> Result<u8, String> {
+    match value {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(format!("invalid part '{value}', expected 1 or 2")),
+    }
+}
+
+fn parse_path(value: &str, option: &str) -> Result<PathBuf, String> {
+    if value.is_empty() {
+        return Err(format!("empty path for {option}"));
+    }
+    Ok(PathBuf::from(value))
+}
+
+fn parse_number<T: FromStr>(value: &str, option: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value '{value}' for {option}"))
+}
+
+fn parse_day(value: &str) -> Result<u8, String> {
+    let day = value.parse::<u8>()
+        .map_err(|_| format!("invalid day '{value}'"))?;
+    if find_puzzle(day).is_none() {
+        return Err(format!("day {day} is not implemented"));
+    }
+    Ok(day)
+}
+
+
+#[derive(Debug, Default)]
+struct RunArgs {
+    day: Option<u8>,
+    part: Option<u8>,
+    input: Option<PathBuf>,
+    all: bool,
+    time: bool,
+    strategy: Option<Strategy>,
+}
+
+
+fn parse_run_args(args: &[String]) -> Result<RunArgs, String> {
+    let mut run_args = RunArgs::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-d" | "--day" => run_args.day = Some(parse_day(next_value(&mut args, "--day")?)?),
+            "-p" | "--part" => run_args.part = Some(parse_part(next_value(&mut args, "--part")?)?),
+            "-i" | "--input" => run_args.input = Some(parse_path(next_value(&mut args, "--input")?, "--input")?),
+            "-a" | "--all" => run_args.all = true,
+            "-t" | "--time" => run_args.time = true,
+            "--strategy" => run_args.strategy = Some(parse_strategy(next_value(&mut args, "--strategy")?)?),
+            other => return Err(format!("unexpected argument '{other}'")),
+        }
+    }
+
+    check_strategy(run_args.day, run_args.strategy)?;
+    match (run_args.all, run_args.day) {
+        (true, Some(_)) => Err("--all and --day cannot be used together".to_string()),
+        (true, None) if run_args.input.is_some() => Err("--input cannot be used with --all".to_string()),
+        (false, None) => Err("one of --day or --all is required".to_string()),
+        _ => Ok(run_args),
+    }
+}
+
+
+#[derive(Debug)]
+struct BenchArgs {
+    day: Option<u8>,
+    input: Option<PathBuf>,
+    iterations: usize,
+    format: Format,
+    strategy: Option<Strategy>,
+}
+
+
+// Every day is benchmarked when no day is given
+fn parse_bench_args(args: &[String]) -> Result<BenchArgs, String> {
+    let mut bench_args = BenchArgs { day: None, input: None, iterations: 10, format: Format::Text, strategy: None };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-d" | "--day" => bench_args.day = Some(parse_day(next_value(&mut args, "--day")?)?),
+            "-i" | "--input" => bench_args.input = Some(parse_path(next_value(&mut args, "--input")?, "--input")?),
+            "-n" | "--iterations" => {
+                let value = next_value(&mut args, "--iterations")?;
+                bench_args.iterations = match value.parse::<usize>() {
+                    Ok(n) if n > 0 => n,
+                    _ => return Err(format!("invalid iteration count '{value}'")),
+                };
+            }
+            "-f" | "--format" => {
+                let value = next_value(&mut args, "--format")?;
+                bench_args.format = value.parse().map_err(|e| format!("{e}"))?;
+            }
+            "--strategy" => bench_args.strategy = Some(parse_strategy(next_value(&mut args, "--strategy")?)?),
+            other => return Err(format!("unexpected argument '{other}'")),
+        }
+    }
+
+    if bench_args.input.is_some() && bench_args.day.is_none() {
+        return Err("--input requires --day".to_string());
+    }
+    check_strategy(bench_args.day, bench_args.strategy)?;
+    Ok(bench_args)
+}
+
+
+#[derive(Debug)]
+struct VerifyArgs {
+    day: Option<u8>,
+    answers: PathBuf,
+}
+
+
+fn parse_verify_args(args: &[String]) -> Result<VerifyArgs, String> {
+    let mut verify_args = VerifyArgs { day: None, answers: PathBuf::from("answers.toml") };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-d" | "--day" => verify_args.day = Some(parse_day(next_value(&mut args, "--day")?)?),
+            "--answers" => verify_args.answers = parse_path(next_value(&mut args, "--answers")?, "--answers")?,
+            other => return Err(format!("unexpected argument '{other}'")),
+        }
+    }
+    Ok(verify_args)
+}
+
+
+// Trace of the monkey simulation of day 11
+#[derive(Debug)]
+struct TraceArgs {
+    part: u8,
+    input: PathBuf,
+    rounds: Option<u64>,
+    format: TraceFormat,
+}
+
+
+fn parse_trace_args(args: &[String]) -> Result<TraceArgs, String> {
+    let mut trace_args = TraceArgs { part: 1, input: default_input(11), rounds: None, format: TraceFormat::Text };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-p" | "--part" => trace_args.part = parse_part(next_value(&mut args, "--part")?)?,
+            "-i" | "--input" => trace_args.input = parse_path(next_value(&mut args, "--input")?, "--input")?,
+            "-r" | "--rounds" => {
+                let value = next_value(&mut args, "--rounds")?;
+                let rounds = value.parse::<u64>()
+                    .map_err(|_| format!("invalid round count '{value}'"))?;
+                trace_args.rounds = Some(rounds);
+            }
+            "-f" | "--format" => {
+                let value = next_value(&mut args, "--format")?;
+                trace_args.format = value.parse().map_err(|e| format!("{e}"))?;
+            }
+            other => return Err(format!("unexpected argument '{other}'")),
+        }
+    }
+    Ok(trace_args)
+}
+
+
+// Rendering of the sensors of day 15
+#[derive(Debug)]
+struct RenderArgs {
+    input: PathBuf,
+    window: BoundingBox<i64>,
+    scale: Option<u64>,
+    format: RenderFormat,
+    output: Option<PathBuf>,
+}
 
-    // println!("Day 06 part 1: {}.", day06::run_part_1("input/day06.txt"));
-    // println!("Day 06 part 2: {}.", day06::run_part_2("input/day06.txt"));
 
-    // println!("Day 07 part 1: {}.", day07::run_part_1("input/day07.txt"));
-    // println!("Day 07 part 2: {}.", day07::run_part_2("input/day07.txt"));
+fn parse_render_args(args: &[String]) -> Result<RenderArgs, String> {
+    let mut render_args = RenderArgs {
+        input: default_input(15),
+        window: BoundingBox { min: Point::new(0, 0), max: Point::new(4000000, 4000000) },
+        scale: None,
+        format: RenderFormat::Text,
+        output: None,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--input" => render_args.input = parse_path(next_value(&mut args, "--input")?, "--input")?,
+            "-w" | "--window" => {
+                let value = next_value(&mut args, "--window")?;
+                let corners: Vec<i64> = value.split(',')
+                    .map(|n| n.trim().parse::<i64>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("invalid window '{value}', expected x1,y1,x2,y2"))?;
+                let [x1, y1, x2, y2] = corners[..] else {
+                    return Err(format!("invalid window '{value}', expected x1,y1,x2,y2"));
+                };
+                render_args.window = BoundingBox::from_points([Point::new(x1, y1), Point::new(x2, y2)]).unwrap();
+            }
+            "-s" | "--scale" => {
+                let value = next_value(&mut args, "--scale")?;
+                render_args.scale = match value.parse::<u64>() {
+                    Ok(n) if n > 0 => Some(n),
+                    _ => return Err(format!("invalid scale '{value}'")),
+                };
+            }
+            "-f" | "--format" => {
+                let value = next_value(&mut args, "--format")?;
+                render_args.format = value.parse().map_err(|e| format!("{e}"))?;
+            }
+            "-o" | "--output" => render_args.output = Some(parse_path(next_value(&mut args, "--output")?, "--output")?),
+            other => return Err(format!("unexpected argument '{other}'")),
+        }
+    }
+    Ok(render_args)
+}
 
-    // println!("Day 08 part 1: {}.", day08::run_part_1("input/day08.txt"));
-    // println!("Day 08 part 2: {}.", day08::run_part_2("input/day08.txt"));
 
-    // println!("Day 09 part 1: {}.", day09::run_part_1("input/day09.txt"));
-    // println!("Day 09 part 2: {}.", day09::run_part_2("input/day09.txt"));
+// Queries on the filesystem of day 07 (du, tree and find)
+#[derive(Debug)]
+struct FsArgs {
+    command: String,
+    input: PathBuf,
+    human: bool,
+    query: FindQuery,
+}
 
-    // println!("Day 10 part 1: {}.", day10::run_part_1("input/day10.txt"));
-    // println!("Day 10 part 2: \n{}.", day10::run_part_2("input/day10.txt"));
 
-    // println!("Day 11 part 1: {}.", day11::run_part_1("input/day11.txt"));
-    // println!("Day 11 part 2: {}.", day11::run_part_2("input/day11.txt"));
+fn parse_fs_args(command: &str, args: &[String]) -> Result<FsArgs, String> {
+    let mut fs_args = FsArgs {
+        command: command.to_string(),
+        input: default_input(7),
+        human: false,
+        query: FindQuery::default(),
+    };
+    let query = &mut fs_args.query;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--input" => fs_args.input = parse_path(next_value(&mut args, "--input")?, "--input")?,
+            "-H" | "--human" => fs_args.human = true,
+            // The other options only filter what find prints
+            "--name" if command == "find" => query.name = Some(next_value(&mut args, "--name")?.to_string()),
+            "--type" if command == "find" => {
+                let value = next_value(&mut args, "--type")?;
+                query.kind = match value {
+                    "f" => Some(EntryKind::File),
+                    "d" => Some(EntryKind::Folder),
+                    _ => return Err(format!("invalid type '{value}', expected f or d")),
+                };
+            }
+            "--min-size" if command == "find" => {
+                let value = next_value(&mut args, "--min-size")?;
+                query.min_size = Some(parse_number(value, "--min-size")?);
+            }
+            "--max-size" if command == "find" => {
+                let value = next_value(&mut args, "--max-size")?;
+                query.max_size = Some(parse_number(value, "--max-size")?);
+            }
+            "--min-depth" if command == "find" => {
+                let value = next_value(&mut args, "--min-depth")?;
+                query.min_depth = Some(parse_number(value, "--min-depth")?);
+            }
+            "--max-depth" if command == "find" => {
+                let value = next_value(&mut args, "--max-depth")?;
+                query.max_depth = Some(parse_number(value, "--max-depth")?);
+            }
+            other => return Err(format!("unexpected argument '{other}'")),
+        }
+    }
+    Ok(fs_args)
+}
 
-    // println!("Day 12 part 1: {}.", day12::run_part_1("input/day12.txt"));
-    // println!("Day 12 part 2: {}.", day12::run_part_2("input/day12.txt"));
 
-    //println!("Day 13 part 1: {}.", day13::run_part_1("input/day13.txt"));
-    //println!("Day 13 part 2: {}.", day13::run_part_2("input/day13.txt"));
+fn default_input(day: u8) -> PathBuf {
+    PathBuf::from(format!("input/day{day:02}.txt"))
+}
+
+
+fn print_answer(day: u8, part: u8, answer: &str, elapsed: Option<Duration>) {
+    let elapsed = elapsed.map(|d| format!(" ({d:.2?})")).unwrap_or_default();
+    // Multi-line answers (like the CRT of day 10) are printed below the header
+    if answer.contains('\n') {
+        println!("Day {day:02} part {part}{elapsed}:\n{}", answer.trim_end());
+    } else {
+        println!("Day {day:02} part {part}: {answer}.{elapsed}");
+    }
+}
+
+
+// The given day or every day, day 15 solving part 2 with the strategy if there is one
+fn select_puzzles(day: Option<u8>, strategy: Option<Strategy>) -> Vec<Box<dyn Puzzle>> {
+    let puzzles: Vec<Box<dyn Puzzle>> = match day {
+        Some(day) => find_puzzle(day).into_iter().collect(),
+        None => registry(),
+    };
+    puzzles.into_iter()
+        .map(|puzzle| match strategy {
+            Some(strategy) if puzzle.day() == Day15::DAY => Box::new(Day15WithStrategy(strategy)),
+            _ => puzzle,
+        })
+        .collect()
+}
+
+
+fn run(args: &RunArgs) -> Result<(), String> {
+    let puzzles = select_puzzles(args.day, args.strategy);
+    let parts: Vec<u8> = match args.part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+
+    for puzzle in puzzles {
+        let day = puzzle.day();
+        let input_path = args.input.clone().unwrap_or_else(|| default_input(day));
+        let input = fs::read_to_string(&input_path)
+            .map_err(|e| format!("cannot read {}: {e}", input_path.display()))?;
+        // The input is parsed once and shared by both parts
+        let start = Instant::now();
+        let parsed = puzzle.parse(&input)
+            .map_err(|e| format!("day {day:02} ({}): {e}", input_path.display()))?;
+        if args.time {
+            println!("Day {day:02} parse: {:.2?}", start.elapsed());
+        }
+        for &part in &parts {
+            let start = Instant::now();
+            let answer = parsed.solve(part)
+                .map_err(|e| format!("day {day:02} part {part}: {e}"))?;
+            let elapsed = args.time.then(|| start.elapsed());
+            print_answer(day, part, &answer.to_string(), elapsed);
+        }
+    }
+    Ok(())
+}
 
-    //println!("Day 14 part 1: {}.", day14::run_part_1("input/day14.txt"));
-    //println!("Day 14 part 2: {}.", day14::run_part_2("input/day14.txt"));
 
-    println!("Day 15 part 1: {}.", day15::run_part_1("input/day15.txt"));
-    //println!("Day 15 part 2: {}.", day15::run_part_2("input/day15.txt"));
+fn run_bench(args: &BenchArgs) -> Result<(), String> {
+    let puzzles = select_puzzles(args.day, args.strategy);
 
+    let mut results = Vec::with_capacity(puzzles.len());
+    for puzzle in puzzles {
+        let day = puzzle.day();
+        let input_path = args.input.clone().unwrap_or_else(|| default_input(day));
+        let input = fs::read_to_string(&input_path)
+            .map_err(|e| format!("cannot read {}: {e}", input_path.display()))?;
+        let result = bench::bench_puzzle(puzzle.as_ref(), &input, args.iterations)
+            .map_err(|e| format!("day {day:02} ({}): {e}", input_path.display()))?;
+        results.push(result);
+    }
+    print!("{}", bench::render(&results, args.format));
+    Ok(())
 }
 
 
+fn run_trace(args: &TraceArgs) -> Result<(), String> {
+    let monkeys = Day11::parse_file(&args.input)
+        .map_err(|e| format!("{}: {e}", args.input.display()))?;
+    let mut simulation = if args.part == 1 { Simulation::PART_1 } else { Simulation::PART_2 };
+    if let Some(rounds) = args.rounds {
+        simulation.rounds = rounds;
+    }
+    let trace = day11::trace(&monkeys, &simulation).map_err(|e| e.to_string())?;
+    print!("{}", trace.render(args.format));
+    Ok(())
+}
+
+
+fn run_render(args: &RenderArgs) -> Result<(), String> {
+    let input = Day15::parse_file(&args.input)
+        .map_err(|e| format!("{}: {e}", args.input.display()))?;
+    let window = &args.window;
+    let scale = args.scale.unwrap_or_else(|| window.width().max(window.height()).div_ceil(1000));
+    let rendering = input.render(window, scale, args.format).map_err(|e| e.to_string())?;
+    match &args.output {
+        Some(path) => fs::write(path, rendering).map_err(|e| format!("{}: {e}", path.display())),
+        None => io::stdout().write_all(&rendering).map_err(|e| e.to_string()),
+    }
+}
+
+
+fn run_fs(args: &FsArgs) -> Result<(), String> {
+    let fs = Day07::parse_file(&args.input)
+        .map_err(|e| format!("{}: {e}", args.input.display()))?;
+    match args.command.as_str() {
+        "du" => print!("{}", fs.du(args.human)),
+        "tree" => print!("{}", fs.tree(args.human)),
+        _ => {
+            for found in fs.find(&args.query) {
+                let size = found.entry.size();
+                let size = if args.human { day07::human_size(size) } else { size.to_string() };
+                println!("{size}\t{}", found.path);
+            }
+        }
+    }
+    Ok(())
+}
+
+
+// Fails when any check fails
+fn run_verify(args: &VerifyArgs) -> Result<(), String> {
+    let expected = answers::load(&args.answers)
+        .map_err(|e| format!("{}: {e}", args.answers.display()))?;
+
+    let mut passed = 0;
+    let mut failed = 0;
+    for entry in expected.iter().filter(|e| args.day.is_none_or(|day| e.day == day)) {
+        for check in answers::verify(entry) {
+            let header = format!("Day {:02} {} part {}", check.day, check.name, check.part);
+            match check.outcome {
+                Outcome::Pass => {
+                    passed += 1;
+                    println!("{header}: ok");
+                }
+                Outcome::Mismatch { expected, actual } => {
+                    failed += 1;
+                    if expected.contains('\n') || actual.contains('\n') {
+                        println!("{header}: MISMATCH\nexpected:\n{expected}\nactual:\n{actual}");
+                    } else {
+                        println!("{header}: MISMATCH, expected {expected}, got {actual}");
+                    }
+                }
+                Outcome::Error(message) => {
+                    failed += 1;
+                    println!("{header}: ERROR {message}");
+                }
+            }
+        }
+    }
+    println!("{passed} passed, {failed} failed");
+    if failed > 0 {
+        return Err(format!("{failed} of {} checks failed", passed + failed));
+    }
+    Ok(())
+}
+
+
+// Parse the arguments of a command and run it. Invalid arguments are reported with the usage.
+fn dispatch<A>(
+    args: &[String],
+    parse: impl FnOnce(&[String]) -> Result<A, String>,
+    run: impl FnOnce(&A) -> Result<(), String>,
+) -> ExitCode {
+    let parsed = match parse(args) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(&parsed) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("run") => dispatch(&args[1..], parse_run_args, run),
+        Some("bench") => dispatch(&args[1..], parse_bench_args, run_bench),
+        Some("verify") => dispatch(&args[1..], parse_verify_args, run_verify),
+        Some("trace") => dispatch(&args[1..], parse_trace_args, run_trace),
+        Some("render") => dispatch(&args[1..], parse_render_args, run_render),
+        Some(command @ ("du" | "tree" | "find")) => {
+            dispatch(&args[1..], |args| parse_fs_args(command, args), run_fs)
+        }
+        Some("-h" | "--help") => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Some(other) => {
+            eprintln!("error: unknown command '{other}'\n\n{USAGE}");
+            ExitCode::FAILURE
+        }
+        None => {
+            eprintln!("{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use nom::IResult;
+use nom::error::ParseError;
+use nom::bytes::complete::tag;
+use nom::character::complete::{line_ending, multispace0};
+use nom::combinator::opt;
+use nom::multi::separated_list1;
+use nom::sequence::{preceded, separated_pair, terminated};
+
+use crate::error::{AocError, Result};
+
+
+/// A combinator that takes a parser `inner` and produces a parser that also consumes leading
+/// whitespace, returning the output of `inner`.
+pub fn ws<'a, F, O, E: ParseError<&'a str>>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
+    where
+    F: FnMut(&'a str) -> IResult<&'a str, O, E>,
+{
+    preceded(
+        multispace0,
+        inner
+    )
+}
+
+/// A combinator that takes a parser `inner` and produces a parser that also consumes one leading
+/// line ending (`\n` or `\r\n`), returning the output of `inner`.
+pub fn cr<'a, F, O, E: ParseError<&'a str>>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
+    where
+    F: FnMut(&'a str) -> IResult<&'a str, O, E>,
+{
+    preceded(
+        line_ending,
+        inner
+    )
+}
+
+/// One or more lines parsed by `line`. The line ending after the last one is optional.
+pub fn lines<'a, F, O, E: ParseError<&'a str>>(line: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>, E>
+    where
+    F: FnMut(&'a str) -> IResult<&'a str, O, E>,
+{
+    terminated(
+        separated_list1(line_ending, line),
+        opt(line_ending)
+    )
+}
+
+/// Coordinates written as `x=<x>, y=<y>`, each of them parsed by `coordinate`.
+pub fn xy<'a, F, O, E: ParseError<&'a str>>(coordinate: F) -> impl FnMut(&'a str) -> IResult<&'a str, (O, O), E>
+    where
+    F: FnMut(&'a str) -> IResult<&'a str, O, E> + Clone,
+{
+    separated_pair(
+        preceded(tag("x="), coordinate.clone()),
+        tag(", "),
+        preceded(tag("y="), coordinate)
+    )
+}
+
+
+/// Run `parser` on the whole `input`. Errors, and input left over by the parser, are reported at
+/// their line and column.
+pub fn finish<'a, O, F>(input: &'a str, parser: F) -> Result<O>
+    where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    finish_slice(input, input, parser)
+}
+
+/// Run `parser` on every line of `input`, each line having to be parsed completely.
+pub fn each_line<'a, O, F>(input: &'a str, mut parser: F) -> Result<Vec<O>>
+    where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    input.lines()
+        .map(|line| finish_slice(input, line, &mut parser))
+        .collect()
+}
+
+// Parse `slice`, a part of `input` which is only used to locate errors
+fn finish_slice<'a, O, F>(input: &'a str, slice: &'a str, mut parser: F) -> Result<O>
+    where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    let (remaining, output) = parser(slice).map_err(|e| AocError::from_nom(input, e))?;
+    if remaining.is_empty() {
+        Ok(output)
+    } else {
+        Err(AocError::trailing_input(input, remaining))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use nom::IResult;
+    use nom::character::complete::{i32, u32};
+
+    fn numbers(input: &str) -> IResult<&str, Vec<u32>> {
+        super::lines(u32)(input)
+    }
+
+    fn position(input: &str) -> IResult<&str, (i32, i32)> {
+        super::xy(i32)(input)
+    }
+
+    #[test]
+    fn test_lines() {
+        assert_eq!(numbers("1\n2\n3\n"), Ok(("", vec![1, 2, 3])));
+        assert_eq!(numbers("1\r\n2"), Ok(("", vec![1, 2])));
+        assert_eq!(numbers("1\n\n2\n"), Ok(("\n2\n", vec![1])));
+    }
+
+    #[test]
+    fn test_xy() {
+        assert_eq!(position("x=3, y=-14: "), Ok((": ", (3, -14))));
+        assert!(position("y=3, x=-14").is_err());
+    }
+
+    #[test]
+    fn test_finish() {
+        assert_eq!(super::finish("1\n2\n", numbers).unwrap(), vec![1, 2]);
+        assert_eq!(
+            super::finish("1\n2\n\n3\n", numbers).unwrap_err().to_string(),
+            "parse error at line 3, column 1: unexpected blank line"
+        );
+        assert_eq!(
+            super::finish("1\nx\n", numbers).unwrap_err().to_string(),
+            "parse error at line 2, column 1: unexpected input 'x' after the end of the data"
+        );
+    }
+
+    #[test]
+    fn test_each_line() {
+        assert_eq!(super::each_line("1\n2\n", u32).unwrap(), vec![1, 2]);
+        assert_eq!(
+            super::each_line("1\n2 3\n", u32).unwrap_err().to_string(),
+            "parse error at line 2, column 2: unexpected input ' 3' after the end of the data"
+        );
+        assert_eq!(
+            super::each_line("1\nx\n", u32).unwrap_err().to_string(),
+            "parse error at line 2, column 1: unexpected input 'x' (Digit)"
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use crate::error::{AocError, Result};
+
+
+/// Answer to one part of a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Signed(i64),
+    Unsigned(u128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Signed(v) => write!(f, "{v}"),
+            Self::Unsigned(v) => write!(f, "{v}"),
+            Self::Text(s) => f.write_str(s),
+        }
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(value: i32) -> Self {
+        Self::Signed(value as i64)
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Self {
+        Self::Signed(value)
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(value: u32) -> Self {
+        Self::Unsigned(value as u128)
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(value: u64) -> Self {
+        Self::Unsigned(value as u128)
+    }
+}
+
+impl From<u128> for Answer {
+    fn from(value: u128) -> Self {
+        Self::Unsigned(value)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+
+/// A puzzle of a given day: the input is parsed once and the parsed model is used to solve both parts.
+pub trait Solution {
+    /// Day of the puzzle (1 to 25)
+    const DAY: u8;
+
+    /// Model of the puzzle input, shared by both parts
+    type Input;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+
+    fn part_1(input: &Self::Input) -> Result<Answer>;
+
+    fn part_2(input: &Self::Input) -> Result<Answer>;
+
+    fn parse_file<P: AsRef<Path>>(filename: P) -> Result<Self::Input> {
+        let input = fs::read_to_string(filename)?;
+        Self::parse(&input)
+    }
+}
+
+
+/// Object-safe view of a `Solution`, so that days of different types can be listed in a registry.
+pub trait Puzzle {
+    fn day(&self) -> u8;
+
+    fn parse(&self, input: &str) -> Result<Box<dyn ParsedPuzzle>>;
+}
+
+/// Parsed input of a `Puzzle`, ready to solve both parts.
+pub trait ParsedPuzzle {
+    fn part_1(&self) -> Result<Answer>;
+
+    fn part_2(&self) -> Result<Answer>;
+
+    fn solve(&self, part: u8) -> Result<Answer> {
+        match part {
+            1 => self.part_1(),
+            2 => self.part_2(),
+            _ => Err(AocError::invalid(format!("invalid part {part}"))),
+        }
+    }
+}
+
+
+struct Registered<S>(PhantomData<S>);
+
+struct Parsed<S: Solution>(S::Input);
+
+impl<S: Solution + 'static> Puzzle for Registered<S> {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn parse(&self, input: &str) -> Result<Box<dyn ParsedPuzzle>> {
+        Ok(Box::new(Parsed::<S>(S::parse(input)?)))
+    }
+}
+
+impl<S: Solution> ParsedPuzzle for Parsed<S> {
+    fn part_1(&self) -> Result<Answer> {
+        S::part_1(&self.0)
+    }
+
+    fn part_2(&self) -> Result<Answer> {
+        S::part_2(&self.0)
+    }
+}
+
+
+/// Wrap a `Solution` into a `Puzzle` that can be stored in the registry.
+pub fn register<S: Solution + 'static>() -> Box<dyn Puzzle> {
+    Box::new(Registered::<S>(PhantomData))
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::{Answer, Solution};
+    use crate::error::{AocError, Result};
+
+    struct Sum;
+
+    impl Solution for Sum {
+        const DAY: u8 = 1;
+        type Input = Vec<u32>;
+
+        fn parse(input: &str) -> Result<Self::Input> {
+            input.split(',')
+                .map(|s| s.parse().map_err(|_| AocError::invalid(s)))
+                .collect()
+        }
+
+        fn part_1(input: &Self::Input) -> Result<Answer> {
+            Ok(input.iter().sum::<u32>().into())
+        }
+
+        fn part_2(input: &Self::Input) -> Result<Answer> {
+            Ok(Answer::Text(format!("{} values", input.len())))
+        }
+    }
+
+    #[test]
+    fn test_registered_puzzle() {
+        let puzzle = super::register::<Sum>();
+        assert_eq!(puzzle.day(), 1);
+        let parsed = puzzle.parse("1,2,3").unwrap();
+        assert_eq!(parsed.solve(1).unwrap(), Answer::Unsigned(6));
+        assert_eq!(parsed.solve(2).unwrap().to_string(), "3 values");
+        assert!(parsed.solve(3).is_err());
+        assert!(puzzle.parse("1,x").is_err());
+    }
+}